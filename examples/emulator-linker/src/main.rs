@@ -6,13 +6,21 @@ extern crate fold;
 
 mod emulator;
 
-use emulator::Emulator;
+use emulator::{EM_RISCV, Emulator};
 use fold::Filter;
 use fold::Fold;
 
 #[fold::chain]
 fn emulator_chain(fold: Fold) -> Fold {
-    fold.select("collect")
-        .after()
-        .register("architecture check", Emulator, Filter::any_object())
+    fold.select("collect").after().register_if(
+        "architecture check",
+        Emulator,
+        Filter::any_object(),
+        |manifold| {
+            manifold
+                .objects
+                .enumerate()
+                .any(|(_, obj)| obj.e_machine == EM_RISCV)
+        },
+    )
 }
@@ -0,0 +1,98 @@
+use alloc::boxed::Box;
+
+use fold::Manifold;
+use fold::Module;
+use fold::{AuxvType, Env};
+
+/// An auxiliary vector entry type which the kernel does not use.
+const AT_UNUSED: AuxvType = AuxvType::new(0x7e57);
+
+#[derive(Debug)]
+struct EnvEditError;
+
+impl From<EnvEditError> for Box<dyn core::fmt::Debug> {
+    fn from(value: EnvEditError) -> Self {
+        Box::new(value)
+    }
+}
+
+/// Edits the arguments, environment and auxiliary vector of the program, checking the result of every mutation.
+///
+/// The program is expected to be started with `one two` as arguments, and `ENV_LINKER_REMOVED` defined.
+pub struct EnvEdit;
+
+impl Module for EnvEdit {
+    fn name(&self) -> &'static str {
+        "env edit"
+    }
+
+    fn process_manifold(
+        &mut self,
+        manifold: &mut Manifold,
+    ) -> Result<(), Box<dyn core::fmt::Debug>> {
+        let env = &mut manifold.env;
+
+        edit_vars(env)?;
+        edit_args(env)?;
+        edit_auxv(env)?;
+
+        log::info!("All environment edits succeeded");
+        Ok(())
+    }
+}
+
+fn check(condition: bool, what: &'static str) -> Result<(), EnvEditError> {
+    if condition {
+        Ok(())
+    } else {
+        log::error!("Unexpected result: {what}");
+        Err(EnvEditError)
+    }
+}
+
+fn edit_vars(env: &mut Env) -> Result<(), Box<dyn core::fmt::Debug>> {
+    check(env.unset("ENV_LINKER_MISSING").is_none(), "unset missing")?;
+    check(env.unset("ENV_LINKER_REMOVED").is_some(), "unset defined")?;
+    check(env.get("ENV_LINKER_REMOVED").is_none(), "get unset")?;
+
+    check(env.set("NAME", b"first")?.is_none(), "set new")?;
+    check(env.set("NAME", b"edited")? == Some(c"first"), "set defined")?;
+    check(env.get("NAME") == Some(c"edited"), "get set")?;
+
+    check(env.set("", b"value").is_err(), "set empty key")?;
+    check(env.set("A=B", b"value").is_err(), "set key with '='")?;
+    check(env.set("NAME", b"nul\0").is_err(), "set value with nul")?;
+
+    Ok(())
+}
+
+fn edit_args(env: &mut Env) -> Result<(), Box<dyn core::fmt::Debug>> {
+    check(env.args.len() == 3, "initial arguments")?;
+
+    // [prog, one, two] -> [prog, first, uno, last]
+    env.insert_arg(env.args.len(), b"last")?;
+    check(env.args.last() == Some(&c"last"), "insert at len")?;
+    env.insert_arg(1, b"first")?;
+    check(env.set_arg(2, b"uno")? == Some(c"one"), "set arg")?;
+    check(env.remove_arg(3) == Some(c"two"), "remove arg")?;
+
+    check(env.set_arg(10, b"none")?.is_none(), "set missing arg")?;
+    check(env.remove_arg(10).is_none(), "remove missing arg")?;
+    check(env.insert_arg(1, b"nul\0").is_err(), "insert arg with nul")?;
+    check(env.args.len() == 4, "final arguments")?;
+
+    Ok(())
+}
+
+fn edit_auxv(env: &mut Env) -> Result<(), Box<dyn core::fmt::Debug>> {
+    let pagesz = env.get_auxv(AuxvType::PAGESZ);
+
+    check(env.set_auxv(AT_UNUSED, 1).is_none(), "set new auxv")?;
+    check(env.set_auxv(AT_UNUSED, 2) == Some(1), "set defined auxv")?;
+    check(env.get_auxv(AT_UNUSED) == Some(2), "get set auxv")?;
+    check(env.remove_auxv(AT_UNUSED) == Some(2), "remove auxv")?;
+    check(env.remove_auxv(AT_UNUSED).is_none(), "remove missing auxv")?;
+    check(env.get_auxv(AuxvType::PAGESZ) == pagesz, "unrelated auxv")?;
+
+    Ok(())
+}
@@ -0,0 +1,17 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+extern crate fold;
+
+mod env_edit;
+
+use env_edit::EnvEdit;
+use fold::{Filter, Fold};
+
+#[fold::chain]
+fn env_chain(fold: Fold) -> Fold {
+    fold.select("start")
+        .before()
+        .register("env edit", EnvEdit, Filter::manifold())
+}
@@ -0,0 +1,104 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ffi::CStr;
+
+use fold::Manifold;
+use fold::Module;
+use fold::arena::Handle;
+use fold::elf::{Section, SymbolTableSection};
+
+pub const SHT_DYNSYM: u32 = 11;
+const SHT_HASH: u32 = 5;
+const SHT_GNU_HASH: u32 = 0x6fff_fff6;
+
+/// A name which none of the samples define.
+const ABSENT: &CStr = c"hash_check_absent";
+
+#[derive(Debug)]
+struct HashMismatch;
+
+impl From<HashMismatch> for Box<dyn core::fmt::Debug> {
+    fn from(value: HashMismatch) -> Self {
+        Box::new(value)
+    }
+}
+
+/// Checks that the GNU and System V hash tables of every dynamic symbol table agree with a linear scan of the table.
+pub struct HashCheck;
+
+impl Module for HashCheck {
+    fn name(&self) -> &'static str {
+        "hash check"
+    }
+
+    fn process_section(
+        &mut self,
+        section: Handle<Section>,
+        manifold: &mut Manifold,
+    ) -> Result<(), Box<dyn core::fmt::Debug>> {
+        let table = manifold[section].as_dynamic_symbol_table()?;
+        let obj = &manifold[manifold[section].obj];
+
+        for hash_table in obj.sections.iter().map(|h| &manifold[*h]) {
+            let style = match hash_table.tag {
+                SHT_GNU_HASH => "GNU",
+                SHT_HASH => "System V",
+                _ => continue,
+            };
+            if hash_table.link_section != Some(section) {
+                continue;
+            }
+
+            let checked = check(&table, hash_table, manifold)?;
+            log::info!(
+                "{}: {style} hash lookups agree with a linear scan of {checked} symbols",
+                obj.display_path()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Looks up every defined symbol of `table` in `hash_table`, and returns the number of symbols checked.
+fn check(
+    table: &SymbolTableSection,
+    hash_table: &Section,
+    manifold: &Manifold,
+) -> Result<usize, Box<dyn core::fmt::Debug>> {
+    let mut checked = 0;
+
+    for index in 1..table.len() {
+        let (name, entry) = table.get_symbol_and_entry(index, manifold)?;
+        // Undefined symbols are not part of GNU hash tables.
+        if entry.st_shndx == 0 || name.is_empty() {
+            continue;
+        }
+
+        if !lookup(hash_table, name).contains(&index) {
+            log::error!("Symbol {name:?} (index {index}) was not found through the hash table");
+            return Err(HashMismatch.into());
+        }
+        checked += 1;
+    }
+
+    // Candidates may share a hash with an absent name, but never the name itself.
+    for index in lookup(hash_table, ABSENT) {
+        if table.get_symbol_name(index, manifold)? == ABSENT {
+            log::error!("Symbol {ABSENT:?} was found through the hash table");
+            return Err(HashMismatch.into());
+        }
+    }
+
+    Ok(checked)
+}
+
+fn lookup(hash_table: &Section, name: &CStr) -> Vec<usize> {
+    if let Ok(gnu_hash) = hash_table.as_gnu_hash_table() {
+        gnu_hash.lookup(name).collect()
+    } else if let Ok(hash) = hash_table.as_hash_table() {
+        hash.lookup(name).collect()
+    } else {
+        Vec::new()
+    }
+}
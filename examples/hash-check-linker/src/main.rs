@@ -0,0 +1,19 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+extern crate fold;
+
+mod hash_check;
+
+use fold::{Filter, Fold};
+use hash_check::{HashCheck, SHT_DYNSYM};
+
+#[fold::chain]
+fn hash_check_chain(fold: Fold) -> Fold {
+    fold.select("collect").after().register(
+        "hash check",
+        HashCheck,
+        Filter::section_type(SHT_DYNSYM),
+    )
+}
@@ -7,11 +7,12 @@ extern crate fold;
 mod seccomp;
 
 use fold::{Filter, Fold};
-use seccomp::Seccomp;
 
 #[fold::chain]
 fn seccomp_chain(fold: Fold) -> Fold {
     fold.select("start")
         .before()
-        .register("syscall restriction", Seccomp, Filter::manifold())
+        .register_fn("syscall restriction", Filter::manifold(), |_, _| {
+            seccomp::install_filter()
+        })
 }
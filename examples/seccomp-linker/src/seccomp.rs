@@ -1,7 +1,5 @@
-use alloc::boxed::Box;
 use alloc::vec::Vec;
 
-use fold::Module;
 use syscalls::{Sysno, syscall};
 
 // Constants from <linux/seccomp.h>, <linux/filter.h>, and <linux/prctl.h>
@@ -37,13 +35,7 @@ struct SockFprog {
 }
 
 #[derive(Debug)]
-struct SeccompError;
-
-impl From<SeccompError> for Box<dyn core::fmt::Debug> {
-    fn from(value: SeccompError) -> Self {
-        Box::new(value)
-    }
-}
+pub struct SeccompError;
 
 // Generate a filter that allows only the listed syscalls
 fn build_seccomp_filter(allowed_syscalls: &[u32]) -> Vec<SockFilter> {
@@ -86,39 +78,29 @@ fn build_seccomp_filter(allowed_syscalls: &[u32]) -> Vec<SockFilter> {
     filters
 }
 
-pub struct Seccomp;
-
-impl Module for Seccomp {
-    fn name(&self) -> &'static str {
-        "seccomp"
-    }
-
-    fn process_manifold(
-        &mut self,
-        _manifold: &mut fold::Manifold,
-    ) -> Result<(), alloc::boxed::Box<dyn core::fmt::Debug>> {
-        // Combine filters for write and exit
-        let mut filters = build_seccomp_filter(&[SYS_WRITE, SYS_EXIT]);
-
-        let mut prog = SockFprog {
-            len: filters.len() as u16,
-            filter: filters.as_mut_ptr(),
-        };
-        unsafe {
-            // Requiered by SECCOMP_SET_MODE_FILTER
-            syscall!(Sysno::prctl, PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0)
-                .map(|_| ())
-                .map_err(|_| Box::from(SeccompError))?;
-
-            // Install the filter using seccomp syscall
-            syscall!(
-                Sysno::seccomp,
-                SECCOMP_SET_MODE_FILTER,
-                0,
-                &mut prog as *mut _ as usize
-            )
+/// Restricts the syscalls of the process to `write` and `exit`.
+pub fn install_filter() -> Result<(), SeccompError> {
+    // Combine filters for write and exit
+    let mut filters = build_seccomp_filter(&[SYS_WRITE, SYS_EXIT]);
+
+    let mut prog = SockFprog {
+        len: filters.len() as u16,
+        filter: filters.as_mut_ptr(),
+    };
+    unsafe {
+        // Requiered by SECCOMP_SET_MODE_FILTER
+        syscall!(Sysno::prctl, PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0)
             .map(|_| ())
-            .map_err(|_| Box::from(SeccompError))
-        }
+            .map_err(|_| SeccompError)?;
+
+        // Install the filter using seccomp syscall
+        syscall!(
+            Sysno::seccomp,
+            SECCOMP_SET_MODE_FILTER,
+            0,
+            &mut prog as *mut _ as usize
+        )
+        .map(|_| ())
+        .map_err(|_| SeccompError)
     }
 }
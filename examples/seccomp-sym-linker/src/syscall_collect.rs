@@ -23,7 +23,7 @@ impl From<SysCollectError> for Box<dyn core::fmt::Debug> {
 }
 
 pub const SECCOMP_SYSCALL_FILTER: ShareMapKey<Vec<u32>> =
-    ShareMapKey::new("seccomp_syscall_filter");
+    ShareMapKey::new("seccomp", "syscall-filter").with_debug();
 
 pub struct SysCollect;
 
@@ -84,11 +84,11 @@ impl TrampolineReloc {
             obj.display_path()
         );
 
-        let base = obj
-            .shared
-            .get(SYSV_LOADER_BASE_ADDR)
-            .copied()
-            .ok_or(SysvError::RelaSectionWithoutVirtualAdresses)? as *mut u8;
+        let base =
+            obj.shared
+                .get(SYSV_LOADER_BASE_ADDR)
+                .copied()
+                .map_err(|_| SysvError::RelaSectionWithoutVirtualAdresses)? as *mut u8;
 
         for rela in ElfItemIterator::<Rela>::from_section(section) {
             let addr: *mut u8 = unsafe { base.add(rela.r_offset as usize) };
@@ -1,16 +1,29 @@
-//! Wrapper around `Vec` that uses type-bound indexes.
+//! Wrapper around `Vec` that uses type-bound, generation-checked indexes.
 
 use alloc::vec::Vec;
-use core::cmp::{Eq, PartialEq};
+use core::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
+use core::fmt;
+use core::hash::{Hash, Hasher};
 use core::iter::{Enumerate, IntoIterator, Iterator};
 use core::marker::PhantomData;
 use core::ops::{Index, IndexMut};
 
 // ————————————————————————————————— Arena —————————————————————————————————— //
 
+/// A slot of an [`Arena`], empty if its element was removed.
+struct Slot<T> {
+    generation: u32,
+    item: Option<T>,
+}
+
 /// Wrapper around `Vec` that uses type-bound indexes.
+///
+/// Elements can be removed, leaving a tombstone in their slot. The generation of the slot is then incremented, such that
+/// handles to the removed element no longer index the arena. Slots are never reused: elements are always iterated in
+/// insertion order.
 pub struct Arena<T> {
-    store: Vec<T>,
+    store: Vec<Slot<T>>,
+    len: usize,
 }
 
 impl<T> Default for Arena<T> {
@@ -22,33 +35,71 @@ impl<T> Default for Arena<T> {
 impl<T> Arena<T> {
     /// Creates an empty `Arena`.
     pub fn new() -> Self {
-        Self { store: Vec::new() }
+        Self {
+            store: Vec::new(),
+            len: 0,
+        }
     }
 
     /// Adds `item` at the end of the Arena.
     pub fn push(&mut self, item: T) -> Handle<T> {
         let idx = self.store.len();
-        self.store.push(item);
-        Handle {
-            idx,
-            _marker: PhantomData,
+        self.store.push(Slot {
+            generation: 0,
+            item: Some(item),
+        });
+        self.len += 1;
+        Handle::new(idx, 0)
+    }
+
+    /// Removes the element at the given handle, returning it if it was present.
+    pub fn remove(&mut self, handle: Handle<T>) -> Option<T> {
+        let slot = self.store.get_mut(handle.idx)?;
+        if slot.generation != handle.generation {
+            return None;
         }
+
+        let item = slot.item.take()?;
+        slot.generation += 1;
+        self.len -= 1;
+        Some(item)
     }
 
     /// Returns the element at the given handle.
     pub fn get(&self, handle: Handle<T>) -> Option<&T> {
-        self.store.get(handle.idx)
+        self.store
+            .get(handle.idx)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.item.as_ref())
     }
 
     /// Returns a mutable reference to the element at the given handle.
     pub fn get_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
-        self.store.get_mut(handle.idx)
+        self.store
+            .get_mut(handle.idx)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.item.as_mut())
     }
 
-    /// Return a handle iterator for the elements of the arena. The iterator will not stop at the end of the Arena and
-    /// may therefore return invalid handles. Elements pushed into the arena during the iteration will be yielded.
-    pub(crate) fn handle_generator(&self) -> HandleGenerator<T> {
-        HandleGenerator::new()
+    /// Returns `true` if `handle` indexes an element of the arena.
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        self.get(handle).is_some()
+    }
+
+    /// Returns the number of elements in the arena, not counting removed ones.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the arena contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a cursor over the handles of the arena. Elements pushed into the arena while the cursor is in use will
+    /// be yielded, and removed elements are skipped.
+    pub(crate) fn cursor(&self) -> Cursor<T> {
+        Cursor::new()
     }
 
     /// Creates an [`EnumHandleIter`] over the arena.
@@ -68,30 +119,73 @@ impl<T> Arena<T> {
 
 // ————————————————————————————————— Handle ————————————————————————————————— //
 
-#[derive(Debug)]
 /// Index into an [`Arena`]. The handle may not index an existing element of the [`Arena`], even it was created from the
-/// same one.
+/// same one: the element may have been removed.
 pub struct Handle<T> {
     idx: usize,
+    generation: u32,
     _marker: PhantomData<T>,
 }
 
 impl<T> Handle<T> {
     /// An invalid handle that will cause a panic if used to access an object.
-    pub const INVALID: Self = Self {
-        idx: usize::MAX,
-        _marker: PhantomData,
-    };
+    pub const INVALID: Self = Self::new(usize::MAX, 0);
+
+    const fn new(idx: usize, generation: u32) -> Self {
+        Self {
+            idx,
+            generation,
+            _marker: PhantomData,
+        }
+    }
 }
 
 impl<T> PartialEq for Handle<T> {
     fn eq(&self, other: &Self) -> bool {
-        self.idx == other.idx
+        self.idx == other.idx && self.generation == other.generation
     }
 }
 
 impl<T> Eq for Handle<T> {}
 
+impl<T> PartialOrd for Handle<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Handle<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.idx, self.generation).cmp(&(other.idx, other.generation))
+    }
+}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.idx.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("idx", &self.idx)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+impl<T> fmt::Display for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.generation == 0 {
+            write!(f, "#{}", self.idx)
+        } else {
+            write!(f, "#{}.{}", self.idx, self.generation)
+        }
+    }
+}
+
 impl<T, K> Index<K> for Arena<T>
 where
     K: Key<T>,
@@ -99,7 +193,11 @@ where
     type Output = T;
 
     fn index(&self, key: K) -> &T {
-        &self.store[key.idx()]
+        let handle = key.handle();
+        match self.get(handle) {
+            Some(item) => item,
+            None => panic!("Invalid or stale handle {handle}"),
+        }
     }
 }
 
@@ -108,7 +206,11 @@ where
     K: Key<T>,
 {
     fn index_mut(&mut self, key: K) -> &mut Self::Output {
-        &mut self.store[key.idx()]
+        let handle = key.handle();
+        match self.get_mut(handle) {
+            Some(item) => item,
+            None => panic!("Invalid or stale handle {handle}"),
+        }
     }
 }
 
@@ -124,101 +226,129 @@ impl<T> Copy for Handle<T> {}
 
 /// Trait used to index [`Arena`]s.
 pub trait Key<T> {
-    fn idx(self) -> usize;
+    fn handle(self) -> Handle<T>;
+
+    fn idx(self) -> usize
+    where
+        Self: Sized,
+    {
+        self.handle().idx
+    }
 }
 
 impl<T> Key<T> for Handle<T> {
-    fn idx(self) -> usize {
-        self.idx
+    fn handle(self) -> Handle<T> {
+        self
     }
 }
 
 // ——————————————————————————————— Iterators ———————————————————————————————— //
 
-/// Handle generator for a given type `T`. It will infinitely yield handles with increasing indexes.
-pub struct HandleGenerator<T> {
+/// Cursor over the handles of an [`Arena`], which does not borrow the arena between calls.
+pub struct Cursor<T> {
     idx: usize,
     _marker: PhantomData<T>,
 }
 
-impl<T> HandleGenerator<T> {
+impl<T> Cursor<T> {
     fn new() -> Self {
         Self {
             idx: 0,
             _marker: PhantomData,
         }
     }
-}
 
-impl<T> Iterator for HandleGenerator<T> {
-    type Item = Handle<T>;
+    /// Returns the handle of the next element of `arena`, skipping removed elements, or `None` if the end of the
+    /// arena is reached.
+    pub fn next(&mut self, arena: &Arena<T>) -> Option<Handle<T>> {
+        while let Some(slot) = arena.store.get(self.idx) {
+            let idx = self.idx;
+            self.idx += 1;
+            if slot.item.is_some() {
+                return Some(Handle::new(idx, slot.generation));
+            }
+        }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let idx = self.idx;
-        self.idx += 1;
-        Some(Handle {
-            idx,
-            _marker: PhantomData,
-        })
+        None
     }
 }
 
 /// Iterator over an arena yielding both elements and their corresponding [`Handle`].
 pub struct EnumHandleIter<'a, T> {
-    inner: Enumerate<core::slice::Iter<'a, T>>,
+    inner: Enumerate<core::slice::Iter<'a, Slot<T>>>,
 }
 
 impl<'a, T> Iterator for EnumHandleIter<'a, T> {
     type Item = (Handle<T>, &'a T);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|(idx, item)| {
-            (
-                Handle {
-                    idx,
-                    _marker: PhantomData,
-                },
-                item,
-            )
+        self.inner.find_map(|(idx, slot)| {
+            let item = slot.item.as_ref()?;
+            Some((Handle::new(idx, slot.generation), item))
         })
     }
 }
 
 /// Iterator over an arena yielding both mutable references to elements and their corresponding [`Handle`].
 pub struct EnumHandleIterMut<'a, T> {
-    inner: Enumerate<core::slice::IterMut<'a, T>>,
+    inner: Enumerate<core::slice::IterMut<'a, Slot<T>>>,
 }
 
 impl<'a, T> Iterator for EnumHandleIterMut<'a, T> {
     type Item = (Handle<T>, &'a mut T);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|(idx, item)| {
-            (
-                Handle {
-                    idx,
-                    _marker: PhantomData,
-                },
-                item,
-            )
+        self.inner.find_map(|(idx, slot)| {
+            let item = slot.item.as_mut()?;
+            Some((Handle::new(idx, slot.generation), item))
         })
     }
 }
 
+/// Iterator over the elements of an arena.
+pub struct Iter<'a, T> {
+    inner: core::slice::Iter<'a, Slot<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.find_map(|slot| slot.item.as_ref())
+    }
+}
+
+/// Iterator over mutable references to the elements of an arena.
+pub struct IterMut<'a, T> {
+    inner: core::slice::IterMut<'a, Slot<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.find_map(|slot| slot.item.as_mut())
+    }
+}
+
 impl<'a, T> IntoIterator for &'a Arena<T> {
     type Item = &'a T;
-    type IntoIter = core::slice::Iter<'a, T>;
+    type IntoIter = Iter<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.store.iter()
+        Iter {
+            inner: self.store.iter(),
+        }
     }
 }
 
 impl<'a, T> IntoIterator for &'a mut Arena<T> {
     type Item = &'a mut T;
-    type IntoIter = core::slice::IterMut<'a, T>;
+    type IntoIter = IterMut<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.store.iter_mut()
+        IterMut {
+            inner: self.store.iter_mut(),
+        }
     }
 }
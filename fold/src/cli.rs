@@ -1,58 +1,289 @@
 //! # Command Line Interface
 
+use alloc::string::String;
+use alloc::vec::Vec;
 use core::ffi::CStr;
+use core::str::FromStr;
+
+use log::LevelFilter;
 
 use crate::env::Env;
-use crate::exit::exit_error;
-use crate::println;
+use crate::exit::{exit, exit_error, Exit};
+use crate::stats::StatsOutput;
+use crate::{logging, println};
+
+/// What the linker does with the target once the chain is set up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// Link and start the target.
+    #[default]
+    Run,
+    /// Link the target without starting it, then print the [`Manifold`][crate::Manifold] (`--inspect`).
+    Inspect,
+    /// Link the target without starting it, then print the loaded objects (`--list`).
+    List,
+    /// Only check that the target is a dynamic executable the linker can handle (`--verify`).
+    Verify,
+}
 
 /// Execution context of the linker.
 pub struct Config {
     /// Path of the executable to link.
     pub target: &'static CStr,
-    /// Execution context.
+    /// Execution context. When the linker is invoked explicitly, the arguments start with the target (or the value of
+    /// `--argv0`), as the program would see them if executed directly.
     pub env: Env,
+    /// Destination of the statistics report, if statistics are enabled.
+    pub stats: Option<StatsOutput>,
+    /// Whether the statistics record the time spent on each element, with `--stats-elements` or the
+    /// `FOLD_STATS_ELEMENTS` environment variable.
+    pub stats_elements: bool,
+    /// What to do with the target.
+    pub mode: Mode,
+    /// Directories searched for libraries before the default search paths, given with `--library-path` or by the
+    /// `FOLD_LIBRARY_PATH` and `LD_LIBRARY_PATH` environment variables.
+    pub library_path: Vec<String>,
+    /// Directory containing musl's `libc.so`, given by the `FOLD_MUSL_PATH` environment variable.
+    pub musl_path: String,
+    /// Libraries to load before the dependencies of the target, given with `--preload` or by the `FOLD_PRELOAD` and
+    /// `LD_PRELOAD` environment variables.
+    pub preload: Vec<String>,
+    /// Whether the system library cache must be ignored, with `--inhibit-cache` or the `FOLD_INHIBIT_CACHE`
+    /// environment variable.
+    pub inhibit_cache: bool,
+    /// Whether PLT entries are bound on their first call, with the `FOLD_LAZY_BINDING` environment variable. Setting
+    /// `LD_BIND_NOW` to a non-empty value forces immediate binding.
+    pub lazy_binding: bool,
+    /// Whether the linker was invoked explicitly (`/lib/linker exe`), rather than by the kernel as the interpreter of
+    /// the target.
+    pub explicit: bool,
 }
 
 /// Parse command line arguments.
+///
+/// Options are only accepted when the linker is invoked explicitly (`/lib/linker [options] [--] exe [args]`). The
+/// linker's own arguments are then removed, such that the program sees its own `argv[0]`. The `FOLD_STATS` environment
+/// variable enables statistics as well.
 pub fn parse(env: Env, loader_name: &str) -> Config {
-    let args = &env.args;
-    if args.is_empty() {
+    if env.args.is_empty() {
         log::error!("No target to execute");
         usage();
         exit_error();
     }
 
-    let Some(target) = find_target(args, loader_name) else {
+    let mut config = Config {
+        target: env.args[0],
+        stats: env
+            .get("FOLD_STATS")
+            .and_then(|value| StatsOutput::parse(value.to_bytes())),
+        stats_elements: false,
+        env,
+        mode: Mode::Run,
+        library_path: Vec::new(),
+        musl_path: String::new(),
+        preload: Vec::new(),
+        inhibit_cache: false,
+        lazy_binding: false,
+        explicit: false,
+    };
+
+    if is_explicit_invocation(&config.env.args, loader_name) {
+        config.explicit = true;
+        parse_options(&mut config);
+    }
+
+    // As with `ld.so`, `--library-path` replaces the library path given by the environment.
+    if config.library_path.is_empty() {
+        config.library_path = library_path(&config.env);
+    }
+    config.preload.extend(preload(&config.env));
+    config.inhibit_cache |= config.env.get("FOLD_INHIBIT_CACHE").is_some();
+    config.stats_elements |= config.env.get("FOLD_STATS_ELEMENTS").is_some();
+    config.lazy_binding = config.env.get("FOLD_LAZY_BINDING").is_some()
+        && config.env.get("LD_BIND_NOW").is_none_or(|v| v.is_empty());
+    config.musl_path = musl_path(&config.env);
+
+    config
+}
+
+/// Variable of the environment, ignored in secure mode.
+fn unsecure_var(env: &Env, key: &str) -> Option<&'static CStr> {
+    let value = env.get(key)?;
+    if env.is_secure() {
+        log::info!("Ignoring {key} in secure mode");
+        return None;
+    }
+    Some(value)
+}
+
+/// Reads the colon-separated library path from `FOLD_LIBRARY_PATH`, followed by `LD_LIBRARY_PATH`.
+fn library_path(env: &Env) -> Vec<String> {
+    ["FOLD_LIBRARY_PATH", "LD_LIBRARY_PATH"]
+        .into_iter()
+        .filter_map(|key| unsecure_var(env, key))
+        .flat_map(|paths| split_list(paths, b":"))
+        .collect()
+}
+
+/// Reads the colon or space separated libraries to preload from `FOLD_PRELOAD`, followed by `LD_PRELOAD`.
+fn preload(env: &Env) -> Vec<String> {
+    ["FOLD_PRELOAD", "LD_PRELOAD"]
+        .into_iter()
+        .filter_map(|key| unsecure_var(env, key))
+        .flat_map(|libs| split_list(libs, b": "))
+        .collect()
+}
+
+/// Reads the location of musl from `FOLD_MUSL_PATH`. It defaults to the value of `FOLD_MUSL_PATH` at build time, or
+/// to the musl build of the source tree.
+fn musl_path(env: &Env) -> String {
+    match unsecure_var(env, "FOLD_MUSL_PATH") {
+        Some(path) => String::from_utf8_lossy(path.to_bytes()).into_owned(),
+        None => option_env!("FOLD_MUSL_PATH")
+            .unwrap_or(concat!(env!("CARGO_MANIFEST_DIR"), "/../musl/lib"))
+            .into(),
+    }
+}
+
+/// Parses the options given to an explicitly invoked linker, and removes the linker's own arguments.
+fn parse_options(config: &mut Config) {
+    let env = &mut config.env;
+    let mut argv0 = None;
+    let mut idx = 1;
+    while let Some(arg) = env.args.get(idx).copied() {
+        let bytes = arg.to_bytes();
+        if bytes == b"--" {
+            idx += 1;
+            break;
+        }
+        if !bytes.starts_with(b"--") {
+            break;
+        }
+
+        let (name, inline) = split_option(arg);
+        let mut value = || match inline {
+            Some(value) => value,
+            None => {
+                idx += 1;
+                env.args.get(idx).copied().unwrap_or_else(|| {
+                    log::error!("Option {arg:?} requires an argument");
+                    usage();
+                    exit_error();
+                })
+            }
+        };
+
+        match name {
+            b"--help" => {
+                usage();
+                exit(Exit::Success);
+            }
+            b"--inspect" => config.mode = Mode::Inspect,
+            b"--list" => config.mode = Mode::List,
+            b"--verify" => config.mode = Mode::Verify,
+            b"--inhibit-cache" => config.inhibit_cache = true,
+            b"--stats" => {
+                config.stats = match inline {
+                    Some(path) => StatsOutput::parse(path.to_bytes()),
+                    None => Some(StatsOutput::Print),
+                }
+            }
+            b"--stats-elements" => config.stats_elements = true,
+            b"--library-path" => config.library_path.extend(split_list(value(), b":")),
+            b"--preload" => config.preload.extend(split_list(value(), b": ")),
+            b"--argv0" => argv0 = Some(value()),
+            b"--log-level" => {
+                let level = value();
+                match level
+                    .to_str()
+                    .ok()
+                    .and_then(|l| LevelFilter::from_str(l).ok())
+                {
+                    Some(level) => log::set_max_level(level),
+                    None => {
+                        log::error!("Invalid log level {level:?}");
+                        usage();
+                        exit_error();
+                    }
+                }
+            }
+            b"--log-file" => {
+                let path = value();
+                if let Err(err) = logging::set_output_file(path) {
+                    log::error!("Unable to open log file {path:?}: {err:?}");
+                    exit_error();
+                }
+            }
+            _ => {
+                log::error!("Unknown option {arg:?}");
+                usage();
+                exit_error();
+            }
+        }
+
+        idx += 1;
+    }
+
+    // Removes the linker's own arguments, the program then finds its own path in `argv[0]`.
+    env.args.drain(..idx);
+    let Some(target) = env.args.first().copied() else {
         log::error!("No target to execute");
         usage();
         exit_error();
     };
+    if let Some(argv0) = argv0 {
+        env.args[0] = argv0;
+    }
 
-    Config { target, env }
+    config.target = target;
 }
 
 /// Print help.
 fn usage() {
-    println!("Spidl Dynamic Loader\n");
-    println!("Usage: spidl <target> [args]");
+    println!("Fold Dynamic Loader\n");
+    println!("Usage: fold [options] [--] <target> [args]\n");
+    println!("Options:");
+    println!("  --library-path <dirs>  Search libraries in the colon-separated <dirs> first");
+    println!("  --preload <libs>       Load the colon or space separated <libs> before any other");
+    println!("  --argv0 <name>         Set the argv[0] seen by the program to <name>");
+    println!("  --inhibit-cache        Do not use the system library cache");
+    println!("  --list                 Link the target without starting it, then list the loaded objects");
+    println!("  --verify               Exit successfully if the target is a dynamic executable fold can link");
+    println!(
+        "  --inspect              Link the target without starting it, then print the loaded objects"
+    );
+    println!("  --stats                Print timings and statistics before starting the target");
+    println!("  --stats=<path>         Write timings and statistics to <path>");
+    println!("  --stats-elements       Include the time spent on each element in the statistics");
+    println!("  --log-level <level>    Set the log level (off, error, warn, info, debug or trace)");
+    println!("  --log-file <path>      Write the logs to <path> instead of the standard output");
+    println!("  --help                 Print this help");
+    println!("\nOption values can also be given as --option=<value>.");
 }
 
-/// Find the program to link.
-fn find_target(args: &[&'static CStr], loader_name: &str) -> Option<&'static CStr> {
+/// Returns `true` if the linker was invoked explicitly (`/lib/linker exe`) rather than by the kernel (`./exe`).
+fn is_explicit_invocation(args: &[&'static CStr], loader_name: &str) -> bool {
     assert!(!args.is_empty());
 
-    // If arg 0 is not self, then it is the target
-    let bytes = args[0].to_bytes();
-    if bytes.len() >= loader_name.len() {
-        let suffix_range = (bytes.len() - loader_name.len())..bytes.len();
-        if &bytes[suffix_range] != loader_name.as_bytes() {
-            return Some(args[0]);
-        }
-    } else {
-        return Some(args[0]);
+    args[0].to_bytes().ends_with(loader_name.as_bytes())
+}
+
+/// Splits `--name=value` into its name and value, if present.
+fn split_option(arg: &'static CStr) -> (&'static [u8], Option<&'static CStr>) {
+    let bytes = arg.to_bytes_with_nul();
+    match bytes.iter().position(|b| *b == b'=') {
+        Some(eq) => (
+            &bytes[..eq],
+            CStr::from_bytes_with_nul(&bytes[eq + 1..]).ok(),
+        ),
+        None => (arg.to_bytes(), None),
     }
+}
 
-    // Otherwise, we are invoked directly, search forthe target
-    args.get(1).copied()
+/// Splits `list` on any of the `separators`, skipping empty items.
+fn split_list<'a>(list: &'a CStr, separators: &'a [u8]) -> impl Iterator<Item = String> + 'a {
+    list.to_bytes()
+        .split(|b| separators.contains(b))
+        .filter(|item| !item.is_empty())
+        .map(|item| String::from_utf8_lossy(item).into_owned())
 }
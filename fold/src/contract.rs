@@ -0,0 +1,258 @@
+//! Declaration of the [`ShareMap`] keys used by modules, and validation of a chain's consistency.
+
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::any::{type_name, TypeId};
+use core::fmt;
+
+use crate::share_map::{KeyName, ShareMap, ShareMapKey};
+
+/// [`ShareMap`] in which a key is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyScope {
+    /// [`Manifold::shared`][crate::Manifold::shared].
+    Manifold,
+    /// [`Object::shared`][crate::elf::Object::shared].
+    Object,
+    /// [`Segment::shared`][crate::elf::Segment::shared].
+    Segment,
+    /// [`Section::shared`][crate::elf::Section::shared].
+    Section,
+}
+
+impl fmt::Display for KeyScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyScope::Manifold => write!(f, "manifold"),
+            KeyScope::Object => write!(f, "object"),
+            KeyScope::Segment => write!(f, "segment"),
+            KeyScope::Section => write!(f, "section"),
+        }
+    }
+}
+
+/// A [`ShareMapKey`] declared in a [`Contract`].
+#[derive(Debug, Clone, Copy)]
+pub struct KeyDecl {
+    /// Fully qualified name of the key.
+    pub key: KeyName,
+    /// [`ShareMap`] in which the key is stored.
+    pub scope: KeyScope,
+    /// Type of the values stored with this key.
+    pub type_id: TypeId,
+    /// Name of the type of the values, for display purposes.
+    pub type_name: &'static str,
+}
+
+impl KeyDecl {
+    /// Creates a declaration for `key` in the given `scope`.
+    pub fn new<T: 'static>(scope: KeyScope, key: ShareMapKey<T>) -> Self {
+        Self {
+            key: key.name(),
+            scope,
+            type_id: TypeId::of::<T>(),
+            type_name: type_name::<T>(),
+        }
+    }
+}
+
+impl fmt::Display for KeyDecl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} key \"{}\" ({})",
+            self.scope, self.key, self.type_name
+        )
+    }
+}
+
+// ———————————————————————————————— Contracts ——————————————————————————————— //
+
+/// Keys of the [`ShareMap`]s a module relies on and publishes.
+///
+/// Modules expose their contract through [`Module::contract`][crate::Module::contract]. Before executing any phase,
+/// [`Fold::run`][crate::Fold::run] checks that every required key is provided by a previous phase (or is present in the
+/// initial [`ShareMap`]), and exits with a report otherwise.
+///
+/// ## Example
+///
+/// ```
+/// Contract::new()
+///     .requires(KeyScope::Object, SYSV_LOADER_BASE_ADDR)
+///     .provides(KeyScope::Manifold, MY_MODULE_RESULT_KEY);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Contract {
+    /// Keys that must be present before the module is executed.
+    pub requires: Vec<KeyDecl>,
+    /// Keys inserted by the module.
+    pub provides: Vec<KeyDecl>,
+}
+
+impl Contract {
+    /// Creates an empty contract.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares a key required by the module.
+    pub fn requires<T: 'static>(mut self, scope: KeyScope, key: ShareMapKey<T>) -> Self {
+        self.requires.push(KeyDecl::new(scope, key));
+        self
+    }
+
+    /// Declares a key provided by the module.
+    pub fn provides<T: 'static>(mut self, scope: KeyScope, key: ShareMapKey<T>) -> Self {
+        self.provides.push(KeyDecl::new(scope, key));
+        self
+    }
+
+    /// Adds the declarations of `other` to `self`.
+    pub fn merge(mut self, other: Contract) -> Self {
+        self.requires.extend(other.requires);
+        self.provides.extend(other.provides);
+        self
+    }
+}
+
+// ——————————————————————————————— Validation ——————————————————————————————— //
+
+/// Inconsistency detected between the contracts of a chain.
+#[derive(Debug)]
+pub enum ContractError {
+    /// A phase requires a key that no previous phase provides.
+    Missing {
+        phase: String,
+        module: &'static str,
+        key: KeyDecl,
+        /// Name of a later phase providing the key, if any.
+        provided_later_by: Option<String>,
+    },
+    /// A phase requires a key that is provided with another type.
+    TypeMismatch {
+        phase: String,
+        module: &'static str,
+        key: KeyDecl,
+        /// Name of the phase providing the key, or `None` if it comes from the initial map.
+        provider: Option<String>,
+        provided_type: &'static str,
+    },
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractError::Missing {
+                phase,
+                module,
+                key,
+                provided_later_by,
+            } => {
+                write!(f, "phase \"{phase}\" (module {module}) requires {key}, ")?;
+                match provided_later_by {
+                    Some(later) => {
+                        write!(f, "but it is only provided by the later phase \"{later}\"")
+                    }
+                    None => write!(f, "but no phase provides it"),
+                }
+            }
+            ContractError::TypeMismatch {
+                phase,
+                module,
+                key,
+                provider,
+                provided_type,
+            } => {
+                write!(
+                    f,
+                    "phase \"{phase}\" (module {module}) requires {key}, but "
+                )?;
+                match provider {
+                    Some(provider) => write!(f, "phase \"{provider}\"")?,
+                    None => write!(f, "the initial share map")?,
+                }
+                write!(f, " provides it as {provided_type}")
+            }
+        }
+    }
+}
+
+/// A phase of a chain, as seen by [`validate`].
+pub(crate) struct ContractedPhase<'a> {
+    pub name: &'a str,
+    pub module: &'static str,
+    pub contract: Contract,
+}
+
+/// Checks that the keys required by each phase are provided by previous phases, or by the `initial` manifold
+/// [`ShareMap`]. `builtin` contains the keys inserted by the driver itself.
+pub(crate) fn validate(
+    phases: &[ContractedPhase],
+    initial: &ShareMap,
+    builtin: &[KeyDecl],
+) -> Result<(), Vec<ContractError>> {
+    let mut errors = Vec::new();
+    // Keys provided so far, along with the name of the providing phase.
+    let mut available: Vec<(KeyDecl, Option<&str>)> = builtin.iter().map(|k| (*k, None)).collect();
+
+    for (idx, phase) in phases.iter().enumerate() {
+        for required in &phase.contract.requires {
+            let provider = available
+                .iter()
+                .rev()
+                .find(|(k, _)| k.key == required.key && k.scope == required.scope);
+
+            let initial_type = (required.scope == KeyScope::Manifold)
+                .then(|| initial.type_id_of(required.key))
+                .flatten();
+
+            match (provider, initial_type) {
+                (Some((provided, _)), _) if provided.type_id == required.type_id => {}
+                (Some((provided, provider)), _) => errors.push(ContractError::TypeMismatch {
+                    phase: phase.name.to_owned(),
+                    module: phase.module,
+                    key: *required,
+                    provider: provider.map(|p| p.to_owned()),
+                    provided_type: provided.type_name,
+                }),
+                (None, Some(type_id)) if type_id == required.type_id => {}
+                (None, Some(_)) => errors.push(ContractError::TypeMismatch {
+                    phase: phase.name.to_owned(),
+                    module: phase.module,
+                    key: *required,
+                    provider: None,
+                    provided_type: "another type",
+                }),
+                (None, None) => errors.push(ContractError::Missing {
+                    phase: phase.name.to_owned(),
+                    module: phase.module,
+                    key: *required,
+                    provided_later_by: phases[idx + 1..]
+                        .iter()
+                        .find(|p| {
+                            p.contract
+                                .provides
+                                .iter()
+                                .any(|k| k.key == required.key && k.scope == required.scope)
+                        })
+                        .map(|p| p.name.to_owned()),
+                }),
+            }
+        }
+
+        available.extend(
+            phase
+                .contract
+                .provides
+                .iter()
+                .map(|k| (*k, Some(phase.name))),
+        );
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
@@ -2,41 +2,55 @@ use alloc::borrow::ToOwned;
 use alloc::boxed::Box;
 use alloc::collections::btree_map::BTreeMap;
 use alloc::ffi::CString;
-use alloc::string::{String, ToString};
+use alloc::string::String;
 use alloc::vec::Vec;
 use core::str::FromStr;
 
-use goblin::elf::program_header::PT_LOAD;
+use goblin::elf::header::EM_X86_64;
+use goblin::elf::program_header::{PT_DYNAMIC, PT_LOAD, PT_TLS};
 use goblin::elf::section_header::{SHT_DYNAMIC, SHT_RELA};
 
 use crate::arena::Handle;
-use crate::cli::Config;
+use crate::cli::{Config, Mode};
+use crate::contract::{self, ContractedPhase, KeyDecl, KeyScope};
 use crate::env::Env;
+use crate::exit::{exit, exit_error, Exit};
 use crate::filters::Filter;
 use crate::manifold::Manifold;
-use crate::module::Module;
-use crate::musl::MuslLocator;
+use crate::module::{Element, FnModule, Module, ModuleResult};
+use crate::musl::{find_libc, MuslInit, MuslLocator, MUSL_LIBC_KEY};
 use crate::object::Object;
+use crate::ordering::{self, Anchor, Constraints, TAG_INIT, TAG_START};
+use crate::policy::{ErrorPolicy, ErrorReport, PhaseError, FOLD_ERROR_REPORT_KEY};
+use crate::stats::{self, Stats, StatsOutput, FOLD_STATS_KEY};
 use crate::sysv::collector::{
-    SysvRemappingCollector, SYSV_COLLECTOR_REMAP_KEY, SYSV_COLLECTOR_SEARCH_PATHS_KEY,
+    SysvRemappingCollector, SYSV_COLLECTOR_CACHE_KEY, SYSV_COLLECTOR_LIBRARY_PATH_KEY,
+    SYSV_COLLECTOR_PRELOAD_KEY, SYSV_COLLECTOR_REMAP_KEY, SYSV_COLLECTOR_SEARCH_PATHS_KEY,
 };
+use crate::sysv::init::SysvInit;
+use crate::sysv::lazy::SYSV_RELOC_LAZY_BINDING_KEY;
+use crate::sysv::ldcache::LdCache;
 use crate::sysv::loader::SysvLoader;
 use crate::sysv::protect::SysvProtect;
 use crate::sysv::relocation::SysvReloc;
-use crate::sysv::start::SysvStart;
+use crate::sysv::start::{SysvStart, SYSV_START_REWRITE_AUXV_KEY};
 use crate::sysv::tls::allocation::TlsAllocator;
 use crate::sysv::tls::collection::TlsCollector;
 use crate::sysv::tls::relocation::TlsRelocator;
-use crate::{cli, file, ShareMap, ShareMapKey};
+use crate::{cli, file, println, ShareMap, ShareMapKey};
 
 type ModuleRef = Box<dyn Module>;
 
+/// Predicate deciding whether a phase is executed, evaluated on the [`Manifold`] when the phase starts.
+pub type Condition = Box<dyn Fn(&Manifold) -> bool>;
+
 // —————————————————————————————— Fold Driver ——————————————————————————————— //
 
 /// Module chain that can be applied to an ELF object file.
 ///
 /// It consists of several [`Module`] that are applied successively to a [`Manifold`]. Each module is registered along
-/// with a [`Filter`], selecting on which elements of the [`Manifold`] the module must be applied to.
+/// with a [`Filter`], selecting on which elements of the [`Manifold`] the module must be applied to, and an
+/// [`ErrorPolicy`] defining how errors returned by the module are handled.
 ///
 /// [`Fold`] can be constructed with either [`Fold::new`], [`Fold::default_chain`] or [`chain`][crate::chain], then modules can be registered
 /// with the object's methods. Modules can also be removed or modified, allowing to easily modify already existing
@@ -51,9 +65,13 @@ struct Phase {
     name: String,
     module: ModuleRef,
     filter: Filter,
+    policy: ErrorPolicy,
+    constraints: Constraints,
+    condition: Option<Condition>,
 }
 
-pub const INITIAL_ELF_KEY: ShareMapKey<Handle<Object>> = ShareMapKey::new("initial-elf");
+pub const INITIAL_ELF_KEY: ShareMapKey<Handle<Object>> =
+    ShareMapKey::new("fold", "initial-elf").with_debug();
 
 impl Fold {
     /// Creates an empty [`Fold`] from the execution context (`env`) and the name of the linker's binary (`linker_name`).
@@ -84,21 +102,37 @@ impl Fold {
                 Filter::section_type(SHT_DYNAMIC),
             )
             .register("load", SysvLoader, Filter::segment_type(PT_LOAD))
-            .register("musl-locator", MuslLocator, Filter::manifold())
-            .register("tls-collector", TlsCollector::new(), Filter::any_object())
-            .register("tls-allocator", TlsAllocator, Filter::manifold())
-            .register(
+            .register_if(
+                "musl-locator",
+                MuslLocator,
+                Filter::manifold(),
+                |manifold| find_libc(manifold).is_some(),
+            )
+            .register_if(
+                "tls-collector",
+                TlsCollector::new(),
+                Filter::any_object(),
+                has_tls_segment,
+            )
+            .register_if(
+                "tls-allocator",
+                TlsAllocator,
+                Filter::manifold(),
+                |manifold| manifold.shared.contains(MUSL_LIBC_KEY),
+            )
+            .register_if(
                 "tls-relocator",
-                TlsRelocator,
+                TlsRelocator::new(),
                 Filter::section_type(SHT_RELA),
+                has_tls_segment,
             )
-            .register(
-                "relocation",
-                SysvReloc::new(),
-                Filter::any_object(), // TODO: match only elf
-            )
+            .register("relocation", SysvReloc::new(), Filter::main_object())
             .register("protect", SysvProtect, Filter::segment_type(PT_LOAD))
-            .register("start", SysvStart, Filter::any_object());
+            .register_if("musl-init", MuslInit, Filter::main_object(), |manifold| {
+                manifold.shared.contains(MUSL_LIBC_KEY)
+            })
+            .register("init", SysvInit, Filter::main_object())
+            .register("start", SysvStart, Filter::main_object());
 
         // Compute the search paths for shared librairies.
         {
@@ -107,19 +141,41 @@ impl Fold {
             } else {
                 "."
             };
-            fold.initial_share_map.insert(
-                SYSV_COLLECTOR_SEARCH_PATHS_KEY,
-                [
-                    cwd,
-                    concat!(env!("CARGO_MANIFEST_DIR"), "/../musl/lib"),
-                    "/lib",
-                    "/lib64",
-                    "/usr/lib/",
-                ]
+            let paths = [cwd, &fold.config.musl_path, "/lib", "/lib64", "/usr/lib/"]
                 .into_iter()
                 .map(|s| s.to_owned())
-                .collect(),
-            );
+                .collect();
+            fold.initial_share_map
+                .insert(SYSV_COLLECTOR_SEARCH_PATHS_KEY, paths);
+
+            let library_path = fold.config.library_path.clone();
+            fold.initial_share_map
+                .insert(SYSV_COLLECTOR_LIBRARY_PATH_KEY, library_path);
+        }
+
+        // Load the system library cache.
+        if !fold.config.inhibit_cache {
+            fold.initial_share_map
+                .insert(SYSV_COLLECTOR_CACHE_KEY, LdCache::load());
+        }
+
+        // Bind PLT entries on first call.
+        if fold.config.lazy_binding {
+            fold.initial_share_map
+                .insert(SYSV_RELOC_LAZY_BINDING_KEY, true);
+        }
+
+        // Describe the target rather than the linker in the auxiliary vector.
+        if fold.config.explicit {
+            fold.initial_share_map
+                .insert(SYSV_START_REWRITE_AUXV_KEY, true);
+        }
+
+        // Libraries to load before the dependencies of the target.
+        {
+            let preload = fold.config.preload.clone();
+            fold.initial_share_map
+                .insert(SYSV_COLLECTOR_PRELOAD_KEY, preload);
         }
 
         // Compute libc remapping to use musl
@@ -202,12 +258,61 @@ impl Fold {
         module: impl Module + 'static,
         item: Filter,
     ) -> Self {
-        self.phases.push(Phase {
-            name: name.as_ref().to_owned(),
-            module: Box::new(module),
-            filter: item,
-        });
+        self.phases.push(Phase::new(name, module, item));
+        self
+    }
+
+    /// Registers a module at the end of the chain, executed only if `condition` holds when the phase starts.
+    ///
+    /// Skipped phases are reported in the log. Keys declared as provided by the module's [`Contract`][crate::Contract]
+    /// are considered available to the following phases, even if the phase may be skipped.
+    pub fn register_if<C: Fn(&Manifold) -> bool + 'static>(
+        mut self,
+        name: impl AsRef<str>,
+        module: impl Module + 'static,
+        item: Filter,
+        condition: C,
+    ) -> Self {
+        let mut phase = Phase::new(name, module, item);
+        phase.condition = Some(Box::new(condition));
+        self.phases.push(phase);
+        self
+    }
+
+    /// Registers a closure at the end of the chain, see [`FnModule::new`]. `name` is used for both the phase and the
+    /// module.
+    pub fn register_fn<R, F>(self, name: &'static str, item: Filter, f: F) -> Self
+    where
+        R: ModuleResult,
+        F: FnMut(Element, &mut Manifold) -> R + 'static,
+    {
+        self.register(name, FnModule::new(name, f), item)
+    }
+
+    /// Enables the collection of timings and statistics, reported to `output` before the program is started.
+    ///
+    /// Statistics can also be enabled with the `FOLD_STATS` environment variable or the `--stats` option.
+    pub fn stats(mut self, output: StatsOutput) -> Self {
+        self.config.stats = Some(output);
+        self
+    }
 
+    /// Records the time spent on each element in the statistics, rather than only aggregating it per phase. Has no
+    /// effect unless statistics are enabled.
+    ///
+    /// Per-element statistics can also be enabled with the `FOLD_STATS_ELEMENTS` environment variable or the
+    /// `--stats-elements` option.
+    pub fn stats_elements(mut self) -> Self {
+        self.config.stats_elements = true;
+        self
+    }
+
+    /// Enables the dry-run mode: phases tagged with [`TAG_INIT`] or [`TAG_START`] are skipped, and the resulting
+    /// [`Manifold`] is printed instead of starting the program.
+    ///
+    /// The dry-run mode can also be enabled with the `--inspect` option.
+    pub fn dry_run(mut self) -> Self {
+        self.config.mode = Mode::Inspect;
         self
     }
 
@@ -219,7 +324,17 @@ impl Fold {
     }
 
     /// Executes the [`Fold`] modules on a [`Manifold`] built from the execution context and target object file.
+    ///
+    /// Phases are first reordered according to their [`Constraints`], then their [`Contract`][crate::Contract]s are validated. The
+    /// linker exits with an error if the constraints can not be satisfied or if a phase requires a key that is not
+    /// provided.
     pub fn run(mut self) {
+        self.sort_phases();
+        self.validate_contracts();
+
+        let stats = self.config.stats.take();
+        let mode = self.config.mode;
+        let dry_run = mode != Mode::Run;
         let mut manifold = Manifold::new(self.config.env, self.initial_share_map);
 
         // Load target
@@ -229,88 +344,369 @@ impl Fold {
         let file = file::map_file(file_fd);
 
         let idx = manifold.add_elf_file(file, target.to_owned());
+        if mode == Mode::Verify {
+            exit(if is_supported(&manifold, idx) {
+                Exit::Success
+            } else {
+                Exit::Error
+            });
+        }
         manifold.shared.insert(INITIAL_ELF_KEY, idx);
+        manifold
+            .shared
+            .insert(FOLD_ERROR_REPORT_KEY, ErrorReport::default());
+        if let Some(output) = stats {
+            let stats = Stats::new(output, self.config.stats_elements);
+            manifold.shared.insert(FOLD_STATS_KEY, stats);
+        }
 
         // Execute each phase
         for phase in &mut self.phases {
+            if dry_run && (phase.has_tag(TAG_START) || phase.has_tag(TAG_INIT)) {
+                log::info!("[ Phase: {} ] skipped (dry-run)", phase.name);
+                continue;
+            }
+            if phase.condition.as_ref().is_some_and(|c| !c(&manifold)) {
+                log::info!("[ Phase: {} ] skipped (condition not met)", phase.name);
+                continue;
+            }
+
             log::info!("[ Phase: {} ]", phase.name);
+
+            let start = manifold
+                .shared
+                .get_mut(FOLD_STATS_KEY)
+                .map(|stats| {
+                    stats.begin_phase(&phase.name);
+                    stats::now()
+                })
+                .ok();
+
             Self::drive_phase(phase, &mut manifold);
+
+            if let (Some(start), Ok(stats)) = (start, manifold.shared.get_mut(FOLD_STATS_KEY)) {
+                stats.end_phase(stats::now() - start);
+            }
         }
-    }
 
-    /// Applies the modules of the phase to every objects.
-    fn drive_phase(phase: &mut Phase, manifold: &mut Manifold) {
-        if phase.filter.matches_manifold() {
-            let module: &mut Box<dyn Module> = &mut phase.module;
-            module.process_manifold(manifold).unwrap();
+        if let Ok(report) = manifold.shared.get(FOLD_ERROR_REPORT_KEY) {
+            report.log();
+        }
+        if let Ok(stats) = manifold.shared.get(FOLD_STATS_KEY) {
+            stats.report();
+        }
+        match mode {
+            Mode::Inspect => println!("{}", manifold.inspect()),
+            Mode::List => println!("{}", manifold.list()),
+            Mode::Run | Mode::Verify => {}
         }
+    }
 
-        for handle in manifold.objects.handle_generator() {
-            if manifold.objects.get(handle).is_none() {
-                // We processed all the objects
-                break;
+    /// Reorders the phases according to their constraints, or exits if they can not be satisfied.
+    fn sort_phases(&mut self) {
+        let constraints = self
+            .phases
+            .iter()
+            .map(|p| {
+                let constraints = p.module.constraints().merge(p.constraints.clone());
+                (p.name.as_str(), constraints)
+            })
+            .collect::<Vec<_>>();
+
+        let order = match ordering::sort(&constraints) {
+            Ok(order) => order,
+            Err(errors) => {
+                for error in errors {
+                    log::error!("Invalid chain: {error}");
+                }
+                exit_error();
             }
+        };
+
+        let mut phases = core::mem::take(&mut self.phases)
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        self.phases = order
+            .into_iter()
+            .filter_map(|idx| phases[idx].take())
+            .collect();
+
+        log::debug!(
+            "Phases order: {:?}",
+            self.phases.iter().map(|p| &p.name).collect::<Vec<_>>()
+        );
+    }
+
+    /// Checks that the keys required by every phase are provided, or exits with a report.
+    fn validate_contracts(&self) {
+        let phases = self
+            .phases
+            .iter()
+            .map(|p| ContractedPhase {
+                name: &p.name,
+                module: p.module.name(),
+                contract: p.module.contract(),
+            })
+            .collect::<Vec<_>>();
+
+        // Keys inserted by `run` before executing the phases.
+        let builtin = [
+            KeyDecl::new(KeyScope::Manifold, INITIAL_ELF_KEY),
+            KeyDecl::new(KeyScope::Manifold, FOLD_ERROR_REPORT_KEY),
+        ];
+
+        if let Err(errors) = contract::validate(&phases, &self.initial_share_map, &builtin) {
+            for error in errors {
+                log::error!("Invalid chain: {error}");
+            }
+            exit_error();
+        }
+    }
 
-            Self::apply_modules(handle, phase, manifold);
+    /// Applies the modules of the phase to every objects, between the [`Module::begin_phase`] and
+    /// [`Module::end_phase`] hooks.
+    fn drive_phase(phase: &mut Phase, manifold: &mut Manifold) {
+        if Self::process(phase, Step::BeginPhase, manifold) == Flow::SkipPhase {
+            return;
         }
+
+        Self::drive_elements(phase, manifold);
+        Self::process(phase, Step::EndPhase, manifold);
     }
 
-    /// Applies all modules to an object.
-    fn apply_modules(obj: Handle<Object>, phase: &mut Phase, manifold: &mut Manifold) {
-        let module: &mut Box<dyn Module> = &mut phase.module;
+    /// Applies the modules of the phase to the manifold and every objects.
+    fn drive_elements(phase: &mut Phase, manifold: &mut Manifold) {
+        if phase.filter.matches_manifold()
+            && Self::process(phase, Element::Manifold.into(), manifold) == Flow::SkipPhase
+        {
+            return;
+        }
 
-        if phase.filter.matches_object(&manifold[obj]) {
-            if let Err(err) = module.process_object(obj, manifold) {
-                log::error!(
-                    "Unable to process object {:?} with module {}: {err:#?}",
-                    manifold.objects.get(obj).map(|o| o.display_path()),
-                    module.name()
-                );
-                panic!();
+        // Objects may be added or removed while the phase is processed.
+        let mut cursor = manifold.objects.cursor();
+        while let Some(handle) = cursor.next(&manifold.objects) {
+            if Self::apply_modules(handle, phase, manifold) == Flow::SkipPhase {
+                return;
             }
         }
+    }
+
+    /// Applies all modules to an object. The object may be removed by the module, in which case its segments and
+    /// sections are not processed.
+    fn apply_modules(obj: Handle<Object>, phase: &mut Phase, manifold: &mut Manifold) -> Flow {
+        if phase.filter.matches_object(manifold, obj)
+            && Self::process(phase, Element::Object(obj).into(), manifold) == Flow::SkipPhase
+        {
+            return Flow::SkipPhase;
+        }
 
         if phase.filter.is_segment_filter() {
             let mut idx = 0;
-            while let Some(handle) = manifold[obj].segments.get(idx) {
+            while let Some(handle) = Self::nth_item(manifold, obj, idx, |o| &o.segments) {
                 idx += 1;
-                if phase
-                    .filter
-                    .matches_segment(&manifold[*handle], &manifold[obj])
+                if phase.filter.matches_segment(manifold, handle)
+                    && Self::process(phase, Element::Segment(handle).into(), manifold)
+                        == Flow::SkipPhase
                 {
-                    if let Err(err) = module.process_segment(*handle, manifold) {
-                        log::error!(
-                            "Unable to process segment #{} of object {:?} with module {}: {err:#?}",
-                            idx,
-                            manifold.objects.get(obj).map(|o| o.display_path()),
-                            module.name()
-                        );
-                        panic!();
-                    }
+                    return Flow::SkipPhase;
                 }
             }
         }
 
         if phase.filter.is_section_filter() {
             let mut idx = 0;
-            while let Some(handle) = manifold[obj].sections.get(idx) {
+            while let Some(handle) = Self::nth_item(manifold, obj, idx, |o| &o.sections) {
                 idx += 1;
-                if phase
-                    .filter
-                    .matches_section(&manifold[*handle], &manifold[obj])
+                if phase.filter.matches_section(manifold, handle)
+                    && Self::process(phase, Element::Section(handle).into(), manifold)
+                        == Flow::SkipPhase
                 {
-                    if let Err(err) = module.process_section(*handle, manifold) {
-                        log::error!(
-                            "Unable to process section #{} of object {:?} with module {}: {err:#?}",
-                            idx,
-                            manifold.objects.get(obj).map(|o| o.display_path()),
-                            module.name()
-                        );
-                        panic!();
-                    }
+                    return Flow::SkipPhase;
                 }
             }
         }
+
+        Flow::Continue
+    }
+
+    /// Returns the `idx`-th segment or section of `obj`, or `None` if there are no more or if `obj` was removed.
+    fn nth_item<T>(
+        manifold: &Manifold,
+        obj: Handle<Object>,
+        idx: usize,
+        items: impl Fn(&Object) -> &Vec<Handle<T>>,
+    ) -> Option<Handle<T>> {
+        let items = items(manifold.objects.get(obj)?);
+        items.get(idx).copied()
+    }
+
+    /// Applies the module of the phase to a single element (or calls one of its phase hooks), handling errors
+    /// according to the phase's [`ErrorPolicy`].
+    fn process(phase: &mut Phase, step: Step, manifold: &mut Manifold) -> Flow {
+        let start = manifold.shared.contains(FOLD_STATS_KEY).then(stats::now);
+        let result = step.apply(phase.module.as_mut(), manifold);
+        if let Some(start) = start {
+            Self::record_timing(step, stats::now() - start, manifold);
+        }
+
+        let Err(error) = result else {
+            return Flow::Continue;
+        };
+
+        let description = step.describe(manifold);
+        log::error!(
+            "Unable to process {description} with module {}: {error:#?}",
+            phase.module.name()
+        );
+        Self::record(phase, phase.module.name(), description, error, manifold);
+
+        match &mut phase.policy {
+            ErrorPolicy::Abort => Self::abort(manifold),
+            ErrorPolicy::SkipItem => Flow::Continue,
+            ErrorPolicy::SkipPhase => {
+                log::warn!("Skipping the remaining elements of phase {}", phase.name);
+                Flow::SkipPhase
+            }
+            ErrorPolicy::Fallback(fallback) => {
+                log::warn!("Falling back to module {}", fallback.name());
+
+                if let Err(error) = step.apply(fallback.as_mut(), manifold) {
+                    let description = step.describe(manifold);
+                    log::error!(
+                        "Unable to process {description} with fallback module {}: {error:#?}",
+                        fallback.name()
+                    );
+                    let name = fallback.name();
+                    Self::record(phase, name, description, error, manifold);
+                    Self::abort(manifold);
+                }
+
+                Flow::Continue
+            }
+        }
+    }
+
+    /// Adds the time spent processing an element to the manifold's [`Stats`]. The element is only described if
+    /// per-element statistics are enabled.
+    fn record_timing(step: Step, elapsed: u64, manifold: &mut Manifold) {
+        let per_element = manifold
+            .shared
+            .get(FOLD_STATS_KEY)
+            .is_ok_and(Stats::per_element);
+        let description = per_element.then(|| step.describe(manifold));
+        if let Ok(stats) = manifold.shared.get_mut(FOLD_STATS_KEY) {
+            stats.record_call(description, elapsed);
+        }
+    }
+
+    /// Adds an error to the manifold's [`ErrorReport`].
+    fn record(
+        phase: &Phase,
+        module: &'static str,
+        element: String,
+        error: Box<dyn core::fmt::Debug>,
+        manifold: &mut Manifold,
+    ) {
+        if let Ok(report) = manifold.shared.get_mut(FOLD_ERROR_REPORT_KEY) {
+            report.push(PhaseError {
+                phase: phase.name.clone(),
+                module,
+                element,
+                error,
+            });
+        }
+    }
+
+    /// Stops the linker after an unrecoverable error.
+    fn abort(manifold: &Manifold) -> ! {
+        if let Ok(report) = manifold.shared.get(FOLD_ERROR_REPORT_KEY) {
+            report.log();
+        }
+
+        exit_error();
+    }
+}
+
+impl Phase {
+    fn new(name: impl AsRef<str>, module: impl Module + 'static, filter: Filter) -> Self {
+        Phase {
+            name: name.as_ref().to_owned(),
+            module: Box::new(module),
+            filter,
+            policy: ErrorPolicy::default(),
+            constraints: Constraints::default(),
+            condition: None,
+        }
+    }
+
+    /// Returns `true` if the phase or its module declare the capability `tag`.
+    fn has_tag(&self, tag: &str) -> bool {
+        self.constraints.tags.iter().any(|t| t == tag)
+            || self.module.constraints().tags.iter().any(|t| t == tag)
+    }
+}
+
+/// Returns `true` if `obj` is a dynamically linked object for the architecture supported by the default chain.
+fn is_supported(manifold: &Manifold, obj: Handle<Object>) -> bool {
+    let obj = &manifold[obj];
+    obj.e_machine == EM_X86_64
+        && obj
+            .segments
+            .iter()
+            .any(|segment| manifold[*segment].tag == PT_DYNAMIC)
+}
+
+/// Returns `true` if an object of the manifold has a `PT_TLS` segment.
+fn has_tls_segment(manifold: &Manifold) -> bool {
+    manifold
+        .segments
+        .enumerate()
+        .any(|(_, segment)| segment.tag == PT_TLS)
+}
+
+/// Outcome of the processing of an element by a phase.
+#[derive(PartialEq, Eq)]
+enum Flow {
+    Continue,
+    SkipPhase,
+}
+
+/// Call made by the driver to the module of a phase.
+#[derive(Clone, Copy)]
+enum Step {
+    BeginPhase,
+    Process(Element),
+    EndPhase,
+}
+
+impl Step {
+    /// Calls the method of `module` corresponding to the step.
+    fn apply(
+        self,
+        module: &mut dyn Module,
+        manifold: &mut Manifold,
+    ) -> Result<(), Box<dyn core::fmt::Debug>> {
+        match self {
+            Step::BeginPhase => module.begin_phase(manifold),
+            Step::Process(element) => element.apply(module, manifold),
+            Step::EndPhase => module.end_phase(manifold),
+        }
+    }
+
+    /// Returns a human readable description of the step.
+    fn describe(self, manifold: &Manifold) -> String {
+        match self {
+            Step::BeginPhase => "beginning of phase".to_owned(),
+            Step::Process(element) => element.describe(manifold),
+            Step::EndPhase => "end of phase".to_owned(),
+        }
+    }
+}
+
+impl From<Element> for Step {
+    fn from(element: Element) -> Self {
+        Step::Process(element)
     }
 }
 
@@ -330,7 +726,8 @@ impl ModuleHandle {
         self.fold
     }
 
-    /// Replaces the selected module with a new name, module and filter.
+    /// Replaces the selected module with a new name, module and filter. The [`ErrorPolicy`] is reset to
+    /// [`ErrorPolicy::Abort`], and the phase's [`Constraints`] and [`Condition`] are cleared.
     pub fn replace(
         mut self,
         name: impl AsRef<str>,
@@ -342,10 +739,52 @@ impl ModuleHandle {
         phase.name = name.as_ref().to_owned();
         phase.module = Box::new(module);
         phase.filter = item;
+        phase.policy = ErrorPolicy::default();
+        phase.constraints = Constraints::default();
+        phase.condition = None;
 
         self.fold
     }
 
+    /// Sets the [`ErrorPolicy`] applied when the selected module returns an error.
+    pub fn on_error(mut self, policy: ErrorPolicy) -> Self {
+        self.fold.phases[self.phase].policy = policy;
+        self
+    }
+
+    /// Adds a capability tag to the selected phase, allowing other phases to be ordered relatively to it with
+    /// [`Anchor::tag`].
+    pub fn tag(mut self, tag: impl AsRef<str>) -> Self {
+        let phase = &mut self.fold.phases[self.phase];
+        phase.constraints = core::mem::take(&mut phase.constraints).tag(tag);
+        self
+    }
+
+    /// Requires the selected phase to run after the phases matched by `anchor`.
+    pub fn runs_after(mut self, anchor: Anchor) -> Self {
+        let phase = &mut self.fold.phases[self.phase];
+        phase.constraints = core::mem::take(&mut phase.constraints).runs_after(anchor);
+        self
+    }
+
+    /// Requires the selected phase to run before the phases matched by `anchor`.
+    pub fn runs_before(mut self, anchor: Anchor) -> Self {
+        let phase = &mut self.fold.phases[self.phase];
+        phase.constraints = core::mem::take(&mut phase.constraints).runs_before(anchor);
+        self
+    }
+
+    /// Executes the selected phase only if `condition` holds when the phase starts, see [`Fold::register_if`].
+    pub fn only_if<C: Fn(&Manifold) -> bool + 'static>(mut self, condition: C) -> Self {
+        self.fold.phases[self.phase].condition = Some(Box::new(condition));
+        self
+    }
+
+    /// Returns the [`Fold`] the handle was created from.
+    pub fn into_fold(self) -> Fold {
+        self.fold
+    }
+
     /// Creates a [`PositionedModuleHandle`] handle between the selected one and the next one, allowing insertion of
     /// modules to be executed after the selected one.
     pub fn after(self) -> PositionedModuleHandle {
@@ -384,24 +823,43 @@ impl PositionedModuleHandle {
 
     /// Registers a new module at the position of the handle.
     pub fn register(
-        mut self,
+        self,
         name: impl AsRef<str>,
         module: impl Module + 'static,
         item: Filter,
     ) -> Fold {
-        self.hdx.fold.phases.insert(
-            self.hdx.phase
-                + match self.position {
-                    CursorPosition::After => 1,
-                    CursorPosition::Before => 0,
-                },
-            Phase {
-                name: name.as_ref().to_string(),
-                module: Box::new(module),
-                filter: item,
-            },
-        );
+        self.insert(Phase::new(name, module, item))
+    }
 
+    /// Registers a new module at the position of the handle, executed only if `condition` holds when the phase starts.
+    /// See [`Fold::register_if`].
+    pub fn register_if<C: Fn(&Manifold) -> bool + 'static>(
+        self,
+        name: impl AsRef<str>,
+        module: impl Module + 'static,
+        item: Filter,
+        condition: C,
+    ) -> Fold {
+        let mut phase = Phase::new(name, module, item);
+        phase.condition = Some(Box::new(condition));
+        self.insert(phase)
+    }
+
+    fn insert(mut self, phase: Phase) -> Fold {
+        let position = match self.position {
+            CursorPosition::After => self.hdx.phase + 1,
+            CursorPosition::Before => self.hdx.phase,
+        };
+        self.hdx.fold.phases.insert(position, phase);
         self.hdx.fold
     }
+
+    /// Registers a closure at the position of the handle, see [`Fold::register_fn`].
+    pub fn register_fn<R, F>(self, name: &'static str, item: Filter, f: F) -> Fold
+    where
+        R: ModuleResult,
+        F: FnMut(Element, &mut Manifold) -> R + 'static,
+    {
+        self.register(name, FnModule::new(name, f), item)
+    }
 }
@@ -118,3 +118,13 @@ impl<T> Clone for ElfItemIterator<'_, T> {
 pub fn sym_bindings(sym: &Sym) -> u8 {
     sym.st_info >> 4
 }
+
+/// Return the `TYPE` value of the symbol.
+pub fn sym_type(sym: &Sym) -> u8 {
+    sym.st_info & 0xf
+}
+
+/// Return the `VISIBILITY` value of the symbol.
+pub fn sym_visibility(sym: &Sym) -> u8 {
+    sym.st_other & 0x3
+}
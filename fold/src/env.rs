@@ -1,10 +1,14 @@
+use alloc::boxed::Box;
+use alloc::ffi::CString;
 use alloc::vec::Vec;
 use core::ffi::{c_char, CStr};
 use core::fmt;
 
+use crate::error::FoldError;
+
 // ——————————————————————————— Auxiliary Vectors ———————————————————————————— //
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 #[repr(C)]
 /// An entry in the auxiliary vector.
 ///
@@ -25,29 +29,177 @@ pub struct AuxvType(u64);
 impl AuxvType {
     /// Marks end of auxiliary vector list.
     pub const NULL: Self = Self(0);
+    /// Entry to be ignored.
+    pub const IGNORE: Self = Self(1);
+    /// File descriptor of the program, if the interpreter must load it.
+    pub const EXECFD: Self = Self(2);
     /// Address of the first program header in memory.
     pub const PHDR: Self = Self(3);
+    /// Size of a program header entry.
+    pub const PHENT: Self = Self(4);
     /// Number of program headers.
     pub const PHNUM: Self = Self(5);
+    /// System page size.
+    pub const PAGESZ: Self = Self(6);
     /// Address where the interpreter (dynamic loader) is mapped.
     pub const BASE: Self = Self(7);
+    /// Flags of the interpreter.
+    pub const FLAGS: Self = Self(8);
     /// Entry point of program.
     pub const ENTRY: Self = Self(9);
-    // TODO: complete with missing types
-    // (<https://refspecs.linuxfoundation.org/LSB_1.3.0/IA64/spec/auxiliaryvector.html>).
+    /// Non-zero if the program is not an ELF file.
+    pub const NOTELF: Self = Self(10);
+    /// Real user ID.
+    pub const UID: Self = Self(11);
+    /// Effective user ID.
+    pub const EUID: Self = Self(12);
+    /// Real group ID.
+    pub const GID: Self = Self(13);
+    /// Effective group ID.
+    pub const EGID: Self = Self(14);
+    /// Address of a string identifying the platform.
+    pub const PLATFORM: Self = Self(15);
+    /// Bit mask of the hardware capabilities of the processor.
+    pub const HWCAP: Self = Self(16);
+    /// Frequency at which `times` counts.
+    pub const CLKTCK: Self = Self(17);
+    /// Control word of the FPU.
+    pub const FPUCW: Self = Self(18);
+    /// Data cache block size.
+    pub const DCACHEBSIZE: Self = Self(19);
+    /// Instruction cache block size.
+    pub const ICACHEBSIZE: Self = Self(20);
+    /// Unified cache block size.
+    pub const UCACHEBSIZE: Self = Self(21);
+    /// Entry to be ignored, on PowerPC.
+    pub const IGNOREPPC: Self = Self(22);
+    /// Non-zero if the program runs with elevated privileges (setuid, setgid or capabilities).
+    pub const SECURE: Self = Self(23);
+    /// Address of a string identifying the real platform, which may differ from [`PLATFORM`][Self::PLATFORM].
+    pub const BASE_PLATFORM: Self = Self(24);
+    /// Address of 16 random bytes.
+    pub const RANDOM: Self = Self(25);
+    /// Extension of [`HWCAP`][Self::HWCAP].
+    pub const HWCAP2: Self = Self(26);
+    /// Size of the features supported by `rseq`.
+    pub const RSEQ_FEATURE_SIZE: Self = Self(27);
+    /// Required alignment of the `rseq` area.
+    pub const RSEQ_ALIGN: Self = Self(28);
+    /// Extension of [`HWCAP`][Self::HWCAP].
+    pub const HWCAP3: Self = Self(29);
+    /// Extension of [`HWCAP`][Self::HWCAP].
+    pub const HWCAP4: Self = Self(30);
+    /// Address of the path of the executed program.
+    pub const EXECFN: Self = Self(31);
+    /// Entry point of the system call function of the vDSO.
+    pub const SYSINFO: Self = Self(32);
+    /// Address of the ELF header of the vDSO.
+    pub const SYSINFO_EHDR: Self = Self(33);
+    /// Shape of the L1 instruction cache.
+    pub const L1I_CACHESHAPE: Self = Self(34);
+    /// Shape of the L1 data cache.
+    pub const L1D_CACHESHAPE: Self = Self(35);
+    /// Shape of the L2 cache.
+    pub const L2_CACHESHAPE: Self = Self(36);
+    /// Shape of the L3 cache.
+    pub const L3_CACHESHAPE: Self = Self(37);
+    /// Size of the L1 instruction cache.
+    pub const L1I_CACHESIZE: Self = Self(40);
+    /// Geometry of the L1 instruction cache.
+    pub const L1I_CACHEGEOMETRY: Self = Self(41);
+    /// Size of the L1 data cache.
+    pub const L1D_CACHESIZE: Self = Self(42);
+    /// Geometry of the L1 data cache.
+    pub const L1D_CACHEGEOMETRY: Self = Self(43);
+    /// Size of the L2 cache.
+    pub const L2_CACHESIZE: Self = Self(44);
+    /// Geometry of the L2 cache.
+    pub const L2_CACHEGEOMETRY: Self = Self(45);
+    /// Size of the L3 cache.
+    pub const L3_CACHESIZE: Self = Self(46);
+    /// Geometry of the L3 cache.
+    pub const L3_CACHEGEOMETRY: Self = Self(47);
+    /// Minimal stack size required by signal delivery.
+    pub const MINSIGSTKSZ: Self = Self(51);
+
+    /// Creates an entry type from its raw value (`AT_*`).
+    pub const fn new(typ: u64) -> Self {
+        Self(typ)
+    }
+
+    /// Returns the raw value of the type (`AT_*`).
+    pub const fn value(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the name of the type, without the `AT_` prefix.
+    fn name(self) -> Option<&'static str> {
+        let name = match self {
+            Self::NULL => "NULL",
+            Self::IGNORE => "IGNORE",
+            Self::EXECFD => "EXECFD",
+            Self::PHDR => "PHDR",
+            Self::PHENT => "PHENT",
+            Self::PHNUM => "PHNUM",
+            Self::PAGESZ => "PAGESZ",
+            Self::BASE => "BASE",
+            Self::FLAGS => "FLAGS",
+            Self::ENTRY => "ENTRY",
+            Self::NOTELF => "NOTELF",
+            Self::UID => "UID",
+            Self::EUID => "EUID",
+            Self::GID => "GID",
+            Self::EGID => "EGID",
+            Self::PLATFORM => "PLATFORM",
+            Self::HWCAP => "HWCAP",
+            Self::CLKTCK => "CLKTCK",
+            Self::FPUCW => "FPUCW",
+            Self::DCACHEBSIZE => "DCACHEBSIZE",
+            Self::ICACHEBSIZE => "ICACHEBSIZE",
+            Self::UCACHEBSIZE => "UCACHEBSIZE",
+            Self::IGNOREPPC => "IGNOREPPC",
+            Self::SECURE => "SECURE",
+            Self::BASE_PLATFORM => "BASE_PLATFORM",
+            Self::RANDOM => "RANDOM",
+            Self::HWCAP2 => "HWCAP2",
+            Self::RSEQ_FEATURE_SIZE => "RSEQ_FEATURE_SIZE",
+            Self::RSEQ_ALIGN => "RSEQ_ALIGN",
+            Self::HWCAP3 => "HWCAP3",
+            Self::HWCAP4 => "HWCAP4",
+            Self::EXECFN => "EXECFN",
+            Self::SYSINFO => "SYSINFO",
+            Self::SYSINFO_EHDR => "SYSINFO_EHDR",
+            Self::L1I_CACHESHAPE => "L1I_CACHESHAPE",
+            Self::L1D_CACHESHAPE => "L1D_CACHESHAPE",
+            Self::L2_CACHESHAPE => "L2_CACHESHAPE",
+            Self::L3_CACHESHAPE => "L3_CACHESHAPE",
+            Self::L1I_CACHESIZE => "L1I_CACHESIZE",
+            Self::L1I_CACHEGEOMETRY => "L1I_CACHEGEOMETRY",
+            Self::L1D_CACHESIZE => "L1D_CACHESIZE",
+            Self::L1D_CACHEGEOMETRY => "L1D_CACHEGEOMETRY",
+            Self::L2_CACHESIZE => "L2_CACHESIZE",
+            Self::L2_CACHEGEOMETRY => "L2_CACHEGEOMETRY",
+            Self::L3_CACHESIZE => "L3_CACHESIZE",
+            Self::L3_CACHEGEOMETRY => "L3_CACHEGEOMETRY",
+            Self::MINSIGSTKSZ => "MINSIGSTKSZ",
+            _ => return None,
+        };
+        Some(name)
+    }
 }
 
 // —————————————————————————————— Environment ——————————————————————————————— //
 
 /// Stores the environment of the process.
 pub struct Env {
-    /// CLI arguments given to the process.
+    /// CLI arguments given to the process. They can be altered with [`Env::set_arg`] and [`Env::insert_arg`].
     pub args: Vec<&'static CStr>,
-    /// List of environment variables of the process, stored in `key=value` format. TODO: change to a tuple of
-    /// `(key, value)` or even a [`alloc::collections::BTreeMap`] ?
+    /// List of environment variables of the process, stored in `key=value` format, in their original order. They are
+    /// usually accessed with [`Env::get`], [`Env::set`], [`Env::unset`] and [`Env::vars`].
     pub envp: Vec<&'static CStr>,
-    /// Auxiliary vector.
-    pub auxv: &'static [Auxv],
+    /// Auxiliary vector, without the terminating [`AuxvType::NULL`] entry. It is copied on the stack of the program,
+    /// and can be altered with [`Env::set_auxv`] and [`Env::remove_auxv`].
+    pub auxv: Vec<Auxv>,
     /// Pointer to the start of the null-terminated argument array.
     pub raw_argv: usize,
     /// Pointer to the start of the null-terminated environments variable array.
@@ -75,6 +227,112 @@ impl Env {
         }
     }
 
+    /// Returns the value of the environment variable `key`, if defined.
+    pub fn get(&self, key: &str) -> Option<&'static CStr> {
+        self.vars()
+            .find(|(k, _)| *k == key.as_bytes())
+            .map(|(_, value)| value)
+    }
+
+    /// Sets the environment variable `key` to `value`, and returns its previous value. A defined variable keeps its
+    /// position in the environment, a new one is added at the end.
+    ///
+    /// Fails with [`FoldError::InvalidString`] if `key` is empty or contains `=`, or if either contains a null byte.
+    pub fn set(&mut self, key: &str, value: &[u8]) -> Result<Option<&'static CStr>, FoldError> {
+        if key.is_empty() || key.contains('=') {
+            return Err(FoldError::InvalidString);
+        }
+        let var = leak_string([key.as_bytes(), b"=", value].concat())?;
+
+        let previous = self.get(key);
+        match self
+            .envp
+            .iter()
+            .position(|v| split_var(v).0 == key.as_bytes())
+        {
+            Some(idx) => self.envp[idx] = var,
+            None => self.envp.push(var),
+        }
+        Ok(previous)
+    }
+
+    /// Removes the environment variable `key`, and returns its value, if it was defined.
+    pub fn unset(&mut self, key: &str) -> Option<&'static CStr> {
+        let previous = self.get(key);
+        self.envp.retain(|v| split_var(v).0 != key.as_bytes());
+        previous
+    }
+
+    /// Returns an iterator over the `(key, value)` pairs of the environment, in order. Variables without `=` have an
+    /// empty value.
+    pub fn vars(&self) -> impl Iterator<Item = (&'static [u8], &'static CStr)> + '_ {
+        self.envp.iter().map(|var| split_var(var))
+    }
+
+    /// Replaces the argument at `index`, and returns the previous one, or `None` if there is no such argument.
+    ///
+    /// Fails with [`FoldError::InvalidString`] if `arg` contains a null byte.
+    pub fn set_arg(
+        &mut self,
+        index: usize,
+        arg: &[u8],
+    ) -> Result<Option<&'static CStr>, FoldError> {
+        let Some(slot) = self.args.get_mut(index) else {
+            return Ok(None);
+        };
+        Ok(Some(core::mem::replace(slot, leak_string(arg.to_vec())?)))
+    }
+
+    /// Inserts an argument at `index`, shifting the following ones. If `index` is past the end, the argument is added
+    /// at the end.
+    ///
+    /// Fails with [`FoldError::InvalidString`] if `arg` contains a null byte.
+    pub fn insert_arg(&mut self, index: usize, arg: &[u8]) -> Result<(), FoldError> {
+        let arg = leak_string(arg.to_vec())?;
+        self.args.insert(index.min(self.args.len()), arg);
+        Ok(())
+    }
+
+    /// Removes the argument at `index`, shifting the following ones, and returns it, or `None` if there is no such
+    /// argument.
+    pub fn remove_arg(&mut self, index: usize) -> Option<&'static CStr> {
+        (index < self.args.len()).then(|| self.args.remove(index))
+    }
+
+    /// Returns the value of the auxiliary vector entry of type `typ`, if present.
+    pub fn get_auxv(&self, typ: AuxvType) -> Option<u64> {
+        self.auxv
+            .iter()
+            .find(|aux| aux.typ == typ)
+            .map(|aux| aux.value)
+    }
+
+    /// Sets the value of the auxiliary vector entry of type `typ`, adding the entry if it is not present. Returns the
+    /// previous value, if any.
+    pub fn set_auxv(&mut self, typ: AuxvType, value: u64) -> Option<u64> {
+        match self.auxv.iter_mut().find(|aux| aux.typ == typ) {
+            Some(aux) => Some(core::mem::replace(&mut aux.value, value)),
+            None => {
+                self.auxv.push(Auxv { typ, value });
+                None
+            }
+        }
+    }
+
+    /// Removes the auxiliary vector entries of type `typ`, and returns the value of the first one, if any.
+    pub fn remove_auxv(&mut self, typ: AuxvType) -> Option<u64> {
+        let value = self.get_auxv(typ);
+        self.auxv.retain(|aux| aux.typ != typ);
+        value
+    }
+
+    /// Returns `true` if the program runs in secure mode (`AT_SECURE`), in which case environment variables altering
+    /// the linker's behavior, such as `LD_LIBRARY_PATH`, must be ignored.
+    pub fn is_secure(&self) -> bool {
+        self.get_auxv(AuxvType::SECURE)
+            .is_some_and(|secure| secure != 0)
+    }
+
     unsafe fn collect_strings(
         base: *const *const c_char,
     ) -> (Vec<&'static CStr>, *const *const c_char) {
@@ -96,7 +354,7 @@ impl Env {
         (strings, base)
     }
 
-    unsafe fn collect_auxv(base: *const Auxv) -> &'static [Auxv] {
+    unsafe fn collect_auxv(base: *const Auxv) -> Vec<Auxv> {
         let mut n = 0;
 
         loop {
@@ -110,10 +368,29 @@ impl Env {
             }
         }
 
-        core::slice::from_raw_parts(base, n)
+        core::slice::from_raw_parts(base, n).to_vec()
     }
 }
 
+/// Splits a `key=value` variable of the environment.
+fn split_var(var: &'static CStr) -> (&'static [u8], &'static CStr) {
+    let bytes = var.to_bytes_with_nul();
+    match bytes.iter().position(|b| *b == b'=') {
+        Some(eq) => (
+            &bytes[..eq],
+            CStr::from_bytes_with_nul(&bytes[eq + 1..]).unwrap_or_default(),
+        ),
+        None => (var.to_bytes(), c""),
+    }
+}
+
+/// Creates a string living as long as the program: the strings of the environment are passed to it, and are never
+/// freed.
+fn leak_string(bytes: Vec<u8>) -> Result<&'static CStr, FoldError> {
+    let string = CString::new(bytes).map_err(|_| FoldError::InvalidString)?;
+    Ok(Box::leak(string.into_boxed_c_str()))
+}
+
 // ———————————————————————————————— Display ————————————————————————————————— //
 
 impl fmt::Debug for Env {
@@ -155,13 +432,9 @@ impl fmt::Debug for Env {
 
 impl fmt::Debug for AuxvType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match *self {
-            Self::NULL => write!(f, "NULL"),
-            Self::PHDR => write!(f, "PHDR"),
-            Self::PHNUM => write!(f, "PHNUM"),
-            Self::BASE => write!(f, "BASE"),
-            Self::ENTRY => write!(f, "ENTRY"),
-            _ => write!(f, "<unknown>"),
+        match self.name() {
+            Some(name) => write!(f, "{name}"),
+            None => write!(f, "<unknown: {}>", self.0),
         }
     }
 }
@@ -1,5 +1,7 @@
 use alloc::ffi::CString;
 
+use crate::share_map::ShareMapError;
+
 #[derive(Debug, Clone)]
 /// Errors that may originate from Fold's internal working.
 pub enum FoldError {
@@ -8,5 +10,11 @@ pub enum FoldError {
     SymbolNotFound(CString),
     OutOfBounds,
     InvalidString,
-    MissingSharedMapEntry(&'static str),
+    SharedMap(ShareMapError),
+}
+
+impl From<ShareMapError> for FoldError {
+    fn from(value: ShareMapError) -> Self {
+        FoldError::SharedMap(value)
+    }
 }
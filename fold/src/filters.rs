@@ -1,26 +1,29 @@
-use alloc::boxed::Box;
-use alloc::vec;
+use alloc::rc::Rc;
 use alloc::vec::Vec;
-use core::ops::BitOr;
+use core::ops::{BitAnd, BitOr, Not};
 
+use crate::arena::Handle;
+use crate::driver::INITIAL_ELF_KEY;
+use crate::glob;
+use crate::manifold::Manifold;
 use crate::object::{Object, Section, Segment};
 
-pub type ObjectPredicate = Box<dyn Fn(&Object) -> bool>;
-pub type SegmentPredicate = Box<dyn Fn(&Object, &Segment) -> bool>;
-pub type SectionPredicate = Box<dyn Fn(&Object, &Section) -> bool>;
+/// Predicate selecting elements of type `T` of a [`Manifold`].
+pub type Predicate<T> = Rc<dyn Fn(&Manifold, Handle<T>) -> bool>;
 
-pub(crate) enum ItemFilter {
-    Manifold,
-    Object(ObjectPredicate),
-    Segment(SegmentPredicate),
-    Section(SectionPredicate),
-}
+pub type ObjectPredicate = Predicate<Object>;
+pub type SegmentPredicate = Predicate<Segment>;
+pub type SectionPredicate = Predicate<Section>;
 
 /// Element filter for applying [`Module`][crate::Module]s from a [`Fold`][crate::Fold].
 ///
-/// A basic filter can match either the whole [`Manifold`][crate::Manifold], or [`Object`], [`Segment`] or [`Section`]
-/// based on a predicate. `Filter` also exposes methods for usual cases, such as `any_*` or per-tag selection. `Filter`s
-/// can be composed using the `|` ([`BitOr`]) operator to extend a [`Module`][crate::Module]'s reach.
+/// A basic filter can match either the whole [`Manifold`], or [`Object`], [`Segment`] or [`Section`] based on a
+/// predicate. `Filter` also exposes methods for usual cases, such as `any_*`, per-tag or per-name selection.
+///
+/// `Filter`s can be composed using the `|` ([`BitOr`]) operator to extend a [`Module`][crate::Module]'s reach, the `&`
+/// ([`BitAnd`]) operator to restrict it, and the `!` ([`Not`]) operator to negate it. When intersecting an object filter
+/// with a segment or section filter, the object filter applies to the object containing the segment or section. Negation
+/// only applies to the kinds of elements matched by the filter: the negation of a section filter matches sections only.
 ///
 /// ## Examples
 ///
@@ -30,78 +33,91 @@ pub(crate) enum ItemFilter {
 ///
 /// // Filter matching the manifold and objects
 /// Filter::manifold() | Filter::any_object();
+///
+/// // Filter matching the `.init_array` sections of the dependencies of the main object, except libc
+/// Filter::section_named(".init_array")
+///     & Filter::dependencies_of(Filter::main_object())
+///     & !Filter::object_path_matches("*/libc.so");
 /// ```
+#[derive(Clone, Default)]
 pub struct Filter {
-    pub(crate) items: Vec<ItemFilter>,
+    manifold: bool,
+    object: Option<ObjectPredicate>,
+    segment: Option<SegmentPredicate>,
+    section: Option<SectionPredicate>,
 }
 
 impl Filter {
     pub(crate) fn matches_manifold(&self) -> bool {
-        self.items.iter().any(|f| matches!(f, ItemFilter::Manifold))
+        self.manifold
     }
 
-    pub(crate) fn matches_object(&self, object: &Object) -> bool {
-        self.items.iter().any(|f| {
-            if let ItemFilter::Object(f) = f {
-                f(object)
-            } else {
-                false
-            }
-        })
+    pub(crate) fn matches_object(&self, manifold: &Manifold, object: Handle<Object>) -> bool {
+        self.object.as_ref().is_some_and(|f| f(manifold, object))
     }
 
     pub(crate) fn is_segment_filter(&self) -> bool {
-        self.items
-            .iter()
-            .any(|f| matches!(f, ItemFilter::Segment(_)))
-    }
-    pub(crate) fn matches_segment(&self, segment: &Segment, object: &Object) -> bool {
-        self.items.iter().any(|f| {
-            if let ItemFilter::Segment(f) = f {
-                f(object, segment)
-            } else {
-                false
-            }
-        })
+        self.segment.is_some()
+    }
+    pub(crate) fn matches_segment(&self, manifold: &Manifold, segment: Handle<Segment>) -> bool {
+        self.segment.as_ref().is_some_and(|f| f(manifold, segment))
     }
 
     pub(crate) fn is_section_filter(&self) -> bool {
-        self.items
-            .iter()
-            .any(|f| matches!(f, ItemFilter::Section(_)))
-    }
-    pub(crate) fn matches_section(&self, section: &Section, object: &Object) -> bool {
-        self.items.iter().any(|f| {
-            if let ItemFilter::Section(f) = f {
-                f(object, section)
-            } else {
-                false
-            }
-        })
+        self.section.is_some()
+    }
+    pub(crate) fn matches_section(&self, manifold: &Manifold, section: Handle<Section>) -> bool {
+        self.section.as_ref().is_some_and(|f| f(manifold, section))
     }
 
-    /// Creates a filter matching the [`Manifold`][crate::Manifold].
+    /// Creates a filter matching the [`Manifold`].
     pub fn manifold() -> Self {
         Filter {
-            items: vec![ItemFilter::Manifold],
+            manifold: true,
+            ..Default::default()
         }
     }
     /// Creates a filter matching [`Object`][crate::elf::Object]s based on a predicate.
     pub fn object<F: Fn(&Object) -> bool + 'static>(pred: F) -> Self {
-        Filter {
-            items: vec![ItemFilter::Object(Box::new(pred))],
-        }
+        Self::object_handle(move |manifold, obj| pred(&manifold[obj]))
     }
     /// Creates a filter matching [`Segment`][crate::elf::Segment]s based on a predicate.
     pub fn segment<F: Fn(&Object, &Segment) -> bool + 'static>(pred: F) -> Self {
-        Filter {
-            items: vec![ItemFilter::Segment(Box::new(pred))],
-        }
+        Self::segment_handle(move |manifold, segment| {
+            let segment = &manifold[segment];
+            pred(&manifold[segment.obj], segment)
+        })
     }
     /// Creates a filter matching [`Section`][crate::elf::Section]s based on a predicate.
     pub fn section<F: Fn(&Object, &Section) -> bool + 'static>(pred: F) -> Self {
+        Self::section_handle(move |manifold, section| {
+            let section = &manifold[section];
+            pred(&manifold[section.obj], section)
+        })
+    }
+
+    /// Creates a filter matching [`Object`][crate::elf::Object]s based on a predicate with access to the whole
+    /// [`Manifold`].
+    pub fn object_handle<F: Fn(&Manifold, Handle<Object>) -> bool + 'static>(pred: F) -> Self {
         Filter {
-            items: vec![ItemFilter::Section(Box::new(pred))],
+            object: Some(Rc::new(pred)),
+            ..Default::default()
+        }
+    }
+    /// Creates a filter matching [`Segment`][crate::elf::Segment]s based on a predicate with access to the whole
+    /// [`Manifold`].
+    pub fn segment_handle<F: Fn(&Manifold, Handle<Segment>) -> bool + 'static>(pred: F) -> Self {
+        Filter {
+            segment: Some(Rc::new(pred)),
+            ..Default::default()
+        }
+    }
+    /// Creates a filter matching [`Section`][crate::elf::Section]s based on a predicate with access to the whole
+    /// [`Manifold`].
+    pub fn section_handle<F: Fn(&Manifold, Handle<Section>) -> bool + 'static>(pred: F) -> Self {
+        Filter {
+            section: Some(Rc::new(pred)),
+            ..Default::default()
         }
     }
 
@@ -126,13 +142,166 @@ impl Filter {
     pub fn section_type(tag: u32) -> Filter {
         Self::section(move |_, s| s.tag == tag)
     }
+    /// Creates a filter matching [`Section`][crate::elf::Section]s with the given name (e.g. `.init_array`).
+    pub fn section_named(name: &str) -> Filter {
+        let name = Vec::from(name.as_bytes());
+        Self::section(move |_, s| s.name.to_bytes() == name)
+    }
+
+    /// Creates a filter matching [`Object`][crate::elf::Object]s whose path matches a glob `pattern`.
+    ///
+    /// The pattern supports `*` (any sequence of characters, including `/`), `?` (any character), and bracket
+    /// expressions such as `[0-9]` or `[!a]`.
+    pub fn object_path_matches(pattern: &str) -> Filter {
+        let pattern = Vec::from(pattern.as_bytes());
+        Self::object(move |o| glob::matches(&pattern, o.path.to_bytes()))
+    }
+    /// Creates a filter matching [`Object`][crate::elf::Object]s of the given ELF type (e.g. `ET_DYN`).
+    pub fn elf_type(elf_type: u16) -> Filter {
+        Self::object(move |o| o.elf_type == elf_type)
+    }
+    /// Creates a filter matching [`Object`][crate::elf::Object]s for the given machine (e.g. `EM_X86_64`).
+    pub fn machine(machine: u16) -> Filter {
+        Self::object(move |o| o.e_machine == machine)
+    }
+    /// Creates a filter matching the object the linker was invoked on, stored under [`INITIAL_ELF_KEY`].
+    pub fn main_object() -> Filter {
+        Self::object_handle(|manifold, obj| manifold.shared.get(INITIAL_ELF_KEY) == Ok(&obj))
+    }
+    /// Creates a filter matching the direct and indirect dependencies of the objects matched by `filter`.
+    pub fn dependencies_of(filter: Filter) -> Filter {
+        Self::object_handle(move |manifold, obj| {
+            let roots = manifold
+                .objects
+                .enumerate()
+                .filter(|(root, _)| filter.matches_object(manifold, *root))
+                .map(|(root, _)| root);
+
+            let mut visited = Vec::new();
+            let mut stack = roots
+                .flat_map(|root| manifold[root].dependencies.iter().copied())
+                .collect::<Vec<_>>();
+            while let Some(dep) = stack.pop() {
+                if dep == obj {
+                    return true;
+                }
+                if !visited.contains(&dep) {
+                    visited.push(dep);
+                    stack.extend(manifold[dep].dependencies.iter().copied());
+                }
+            }
+
+            false
+        })
+    }
+}
+
+/// Combines two optional predicates, matching if `op` holds for their results.
+fn combine<T: 'static>(
+    lhs: Option<Predicate<T>>,
+    rhs: Option<Predicate<T>>,
+    op: fn(bool, bool) -> bool,
+) -> Option<Predicate<T>> {
+    match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) => Some(Rc::new(move |m: &Manifold, h| op(lhs(m, h), rhs(m, h)))),
+        (lhs, rhs) => lhs.or(rhs),
+    }
+}
+
+/// Lifts an object predicate to the segments or sections of the matching objects.
+fn lift<T: 'static>(
+    pred: &Option<ObjectPredicate>,
+    owner: fn(&Manifold, Handle<T>) -> Handle<Object>,
+) -> Option<Predicate<T>> {
+    let pred = pred.clone()?;
+    Some(Rc::new(move |m: &Manifold, h| pred(m, owner(m, h))))
+}
+
+/// Intersects the segment or section level of two filters, lifting object predicates if only one side has a predicate
+/// for that level.
+fn intersect<T: 'static>(
+    lhs: Option<Predicate<T>>,
+    rhs: Option<Predicate<T>>,
+    lhs_lifted: Option<Predicate<T>>,
+    rhs_lifted: Option<Predicate<T>>,
+) -> Option<Predicate<T>> {
+    match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) => combine(Some(lhs), Some(rhs), |a, b| a && b),
+        (Some(lhs), None) => {
+            rhs_lifted.and_then(|rhs| combine(Some(lhs), Some(rhs), |a, b| a && b))
+        }
+        (None, Some(rhs)) => {
+            lhs_lifted.and_then(|lhs| combine(Some(lhs), Some(rhs), |a, b| a && b))
+        }
+        (None, None) => None,
+    }
+}
+
+fn segment_owner(manifold: &Manifold, segment: Handle<Segment>) -> Handle<Object> {
+    manifold[segment].obj
+}
+
+fn section_owner(manifold: &Manifold, section: Handle<Section>) -> Handle<Object> {
+    manifold[section].obj
 }
 
 impl BitOr for Filter {
     type Output = Self;
 
-    fn bitor(mut self, mut rhs: Self) -> Self::Output {
-        self.items.append(&mut rhs.items);
-        self
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Filter {
+            manifold: self.manifold || rhs.manifold,
+            object: combine(self.object, rhs.object, |a, b| a || b),
+            segment: combine(self.segment, rhs.segment, |a, b| a || b),
+            section: combine(self.section, rhs.section, |a, b| a || b),
+        }
+    }
+}
+
+impl BitAnd for Filter {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        let segment = intersect(
+            self.segment,
+            rhs.segment,
+            lift(&self.object, segment_owner),
+            lift(&rhs.object, segment_owner),
+        );
+        let section = intersect(
+            self.section,
+            rhs.section,
+            lift(&self.object, section_owner),
+            lift(&rhs.object, section_owner),
+        );
+        let object = match (self.object, rhs.object) {
+            (Some(lhs), Some(rhs)) => combine(Some(lhs), Some(rhs), |a, b| a && b),
+            _ => None,
+        };
+
+        Filter {
+            manifold: self.manifold && rhs.manifold,
+            object,
+            segment,
+            section,
+        }
+    }
+}
+
+impl Not for Filter {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        fn negate<T: 'static>(pred: Option<Predicate<T>>) -> Option<Predicate<T>> {
+            let pred = pred?;
+            Some(Rc::new(move |m: &Manifold, h| !pred(m, h)))
+        }
+
+        Filter {
+            manifold: false,
+            object: negate(self.object),
+            segment: negate(self.segment),
+            section: negate(self.section),
+        }
     }
 }
@@ -0,0 +1,75 @@
+//! Shell-style pattern matching.
+
+/// Returns `true` if `text` matches the glob `pattern`.
+///
+/// Supported syntax is `*` (any sequence of bytes, including `/`), `?` (any byte), and bracket expressions (`[abc]`,
+/// `[a-z]`, negated with `[!abc]`). Other bytes match themselves.
+pub(crate) fn matches(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Position in the pattern and the text after the last `*`, to backtrack on mismatch.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        let matched = match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+                continue;
+            }
+            Some(b'?') => Some(p + 1),
+            Some(b'[') => match_bracket(&pattern[p..], text[t]).map(|len| p + len),
+            Some(c) if *c == text[t] => Some(p + 1),
+            _ => None,
+        };
+
+        match (matched, backtrack) {
+            (Some(next), _) => {
+                p = next;
+                t += 1;
+            }
+            (None, Some((star_p, star_t))) => {
+                // Let the last `*` consume one more byte.
+                p = star_p;
+                t = star_t + 1;
+                backtrack = Some((star_p, star_t + 1));
+            }
+            (None, None) => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == b'*')
+}
+
+/// Matches `c` against the bracket expression at the start of `pattern`, returning the length of the expression if it
+/// matches.
+///
+/// An unterminated bracket is treated as a literal `[`.
+fn match_bracket(pattern: &[u8], c: u8) -> Option<usize> {
+    let negated = matches!(pattern.get(1), Some(b'!') | Some(b'^'));
+    let start = if negated { 2 } else { 1 };
+
+    // A `]` right after the opening bracket is part of the set.
+    let Some(end) = pattern
+        .iter()
+        .skip(start + 1)
+        .position(|b| *b == b']')
+        .map(|pos| pos + start + 1)
+    else {
+        return (c == b'[').then_some(1);
+    };
+
+    let set = &pattern[start..end];
+    let mut found = false;
+    let mut idx = 0;
+    while idx < set.len() {
+        if idx + 2 < set.len() && set[idx + 1] == b'-' {
+            found |= (set[idx]..=set[idx + 2]).contains(&c);
+            idx += 3;
+        } else {
+            found |= set[idx] == c;
+            idx += 1;
+        }
+    }
+
+    (found != negated).then_some(end + 1)
+}
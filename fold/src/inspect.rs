@@ -0,0 +1,170 @@
+//! Human readable dumps of a [`Manifold`], used by the dry-run modes of [`Fold`][crate::Fold].
+
+use core::fmt;
+
+use goblin::elf::header::et_to_str;
+use goblin::elf::program_header::{pt_to_str, PF_R, PF_W, PF_X};
+
+use crate::driver::INITIAL_ELF_KEY;
+use crate::manifold::Manifold;
+use crate::object::Segment;
+use crate::share_map::ShareMap;
+use crate::sysv::loader::{SYSV_LOADER_BASE_ADDR, SYSV_LOADER_MAPPING};
+use crate::sysv::tls::collection::TLS_MODULE_KEY;
+
+/// Displays the objects of a [`Manifold`]: paths, base addresses, dependencies, segments with their mapped range and
+/// protections, TLS modules, and the entries of every [`ShareMap`] with their type (and value, for keys created with
+/// [`ShareMapKey::with_debug`][crate::ShareMapKey::with_debug]).
+///
+/// Created with [`Manifold::inspect`].
+pub struct Inspect<'a> {
+    manifold: &'a Manifold,
+}
+
+/// Lists the objects loaded in a [`Manifold`] besides the main one, with their base address, in the format of
+/// `ld.so --list`.
+///
+/// Created with [`Manifold::list`].
+pub struct List<'a> {
+    manifold: &'a Manifold,
+}
+
+impl Manifold {
+    /// Returns a human readable dump of the manifold.
+    pub fn inspect(&self) -> Inspect<'_> {
+        Inspect { manifold: self }
+    }
+
+    /// Returns the list of the loaded objects, similar to `ld.so --list`.
+    pub fn list(&self) -> List<'_> {
+        List { manifold: self }
+    }
+}
+
+impl fmt::Display for List<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let main = self.manifold.shared.get(INITIAL_ELF_KEY).ok();
+        let objects = self
+            .manifold
+            .objects
+            .enumerate()
+            .filter(|(handle, _)| Some(handle) != main);
+
+        for (idx, (_, obj)) in objects.enumerate() {
+            if idx != 0 {
+                writeln!(f)?;
+            }
+
+            let path = obj.display_path();
+            let name = path.rsplit('/').next().unwrap_or(path);
+            write!(f, "\t{name} => {path}")?;
+            if let Ok(base) = obj.shared.get(SYSV_LOADER_BASE_ADDR) {
+                write!(f, " ({base:#x})")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Inspect<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let manifold = self.manifold;
+
+        writeln!(f, "Manifold")?;
+        write!(f, "  shared: ")?;
+        shared(f, &manifold.shared, "    ")?;
+
+        for (handle, obj) in manifold.objects.enumerate() {
+            writeln!(f, "\n[{handle}] {}", obj.display_path())?;
+            writeln!(f, "  type: {}", et_to_str(obj.elf_type))?;
+            match obj.shared.get(SYSV_LOADER_BASE_ADDR) {
+                Ok(base) => writeln!(f, "  base: {base:#x}")?,
+                Err(_) => writeln!(f, "  base: <not loaded>")?,
+            }
+
+            write!(f, "  dependencies:")?;
+            if obj.dependencies.is_empty() {
+                write!(f, " none")?;
+            }
+            for dep in &obj.dependencies {
+                write!(f, " [{dep}]")?;
+            }
+            writeln!(f)?;
+
+            if let Ok(tls) = obj.shared.get(TLS_MODULE_KEY) {
+                writeln!(f, "  tls: module {}, offset -{:#x}", tls.id, tls.tls_offset)?;
+            }
+
+            writeln!(f, "  segments:")?;
+            for segment in &obj.segments {
+                segment_line(f, &manifold[*segment])?;
+            }
+
+            write!(f, "  shared: ")?;
+            shared(f, &obj.shared, "    ")?;
+
+            let sections = obj
+                .sections
+                .iter()
+                .map(|s| &manifold[*s])
+                .filter(|s| s.shared.keys().next().is_some());
+            for section in sections {
+                write!(f, "  section {:?} shared: ", section.name)?;
+                shared(f, &section.shared, "    ")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes a line describing `segment`.
+fn segment_line(f: &mut fmt::Formatter<'_>, segment: &Segment) -> fmt::Result {
+    let prot = |flag: u32, c: char| if segment.flags & flag != 0 { c } else { '-' };
+
+    write!(
+        f,
+        "    {:<16} vaddr {:#010x} memsz {:#08x} {}{}{}",
+        pt_to_str(segment.tag),
+        segment.vaddr,
+        segment.mem_size,
+        prot(PF_R, 'r'),
+        prot(PF_W, 'w'),
+        prot(PF_X, 'x'),
+    )?;
+
+    if let Ok(mapping) = segment.shared.get(SYSV_LOADER_MAPPING) {
+        let start = mapping.bytes().as_ptr() as usize;
+        write!(
+            f,
+            " mapped {:#x}-{:#x}",
+            start,
+            start + mapping.bytes().len()
+        )?;
+    }
+
+    let mut shared = segment.shared.keys().peekable();
+    if shared.peek().is_some() {
+        write!(f, " shared:")?;
+        for key in shared {
+            write!(f, " {key}")?;
+        }
+    }
+
+    writeln!(f)
+}
+
+/// Writes the entries of `map`, one per line, with their type and value if available.
+fn shared(f: &mut fmt::Formatter<'_>, map: &ShareMap, indent: &str) -> fmt::Result {
+    let mut entries = map.entries().peekable();
+    if entries.peek().is_none() {
+        return writeln!(f, "none");
+    }
+
+    writeln!(f)?;
+    for entry in entries {
+        writeln!(f, "{indent}{entry}")?;
+    }
+    Ok(())
+}
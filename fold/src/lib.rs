@@ -9,15 +9,22 @@ use core::panic::PanicInfo;
 
 mod allocator;
 mod cli;
+mod contract;
 mod driver;
 mod env;
 mod error;
 mod exit;
 mod filters;
+mod glob;
+mod inspect;
 mod manifold;
 mod module;
 mod object;
+mod ordering;
+mod policy;
+mod scope;
 mod share_map;
+mod stats;
 
 pub mod arena;
 pub mod elf;
@@ -28,16 +35,22 @@ pub mod sysv;
 
 pub use allocator::*;
 pub use cli::*;
+pub use contract::*;
 pub use driver::*;
 pub use env::*;
 pub use error::*;
 pub use exit::*;
 pub use filters::*;
+pub use inspect::*;
 pub use log;
 pub use macros::chain;
 pub use manifold::*;
 pub use module::*;
+pub use ordering::*;
+pub use policy::*;
+pub use scope::*;
 pub use share_map::*;
+pub use stats::*;
 
 #[macro_export]
 /// Creates an entrypoint from a function receiving an [`Env`] as parameter. Superseeded by the [`chain`] macro.
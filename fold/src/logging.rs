@@ -1,10 +1,14 @@
 //! Re-implementation of log and print macros in a no-std context.
 
-use core::fmt;
-use core::sync::atomic::{AtomicBool, Ordering};
+use core::ffi::CStr;
+use core::fmt::{self, Write};
+use core::sync::atomic::{AtomicBool, AtomicI32, Ordering};
 
 use log::{LevelFilter, Metadata, Record};
-use rustix::{io, stdio};
+use rustix::fd::{BorrowedFd, IntoRawFd};
+use rustix::fs::{self, Mode, OFlags};
+use rustix::io::{self, Errno};
+use rustix::stdio;
 
 // ———————————————————————————————— Println ————————————————————————————————— //
 
@@ -59,6 +63,25 @@ struct Logger;
 
 static LOGGER: Logger = Logger;
 static IS_INITIALIZED: AtomicBool = AtomicBool::new(false);
+/// File descriptor receiving the log records, or `-1` for the standard output.
+static OUTPUT_FD: AtomicI32 = AtomicI32::new(-1);
+
+/// Destination of the log records, see [`set_output_file`].
+struct Output;
+
+impl fmt::Write for Output {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match OUTPUT_FD.load(Ordering::Relaxed) {
+            -1 => Stdout.write_str(s),
+            fd => {
+                // Safety: the descriptor is owned by the logger and never closed.
+                let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+                io::write(fd, s.as_bytes()).map_err(|_| fmt::Error)?;
+                Ok(())
+            }
+        }
+    }
+}
 
 impl log::Log for Logger {
     fn enabled(&self, _metadata: &Metadata) -> bool {
@@ -66,7 +89,7 @@ impl log::Log for Logger {
     }
 
     fn log(&self, record: &Record) {
-        println!("[{}] {}", record.level(), record.args());
+        let _ = writeln!(Output, "[{}] {}", record.level(), record.args());
     }
 
     fn flush(&self) {}
@@ -84,3 +107,15 @@ pub fn init(level: LevelFilter) {
         }
     };
 }
+
+/// Writes the log records to the file at `path` instead of the standard output. The file is created if needed, and
+/// records are appended to it.
+pub fn set_output_file(path: &CStr) -> Result<(), Errno> {
+    let fd = fs::open(
+        path,
+        OFlags::WRONLY | OFlags::CREATE | OFlags::APPEND | OFlags::CLOEXEC,
+        Mode::from_raw_mode(0o644),
+    )?;
+    OUTPUT_FD.store(fd.into_raw_fd(), Ordering::Relaxed);
+    Ok(())
+}
@@ -6,14 +6,13 @@ use alloc::vec::Vec;
 use core::ffi::CStr;
 use core::ops::{Index, IndexMut};
 
-use goblin::elf::sym::{STB_GLOBAL, STB_LOCAL, STB_WEAK};
 use goblin::elf64::sym::Sym;
 
 use crate::arena::{Arena, Handle};
-use crate::elf::sym_bindings;
 use crate::error::FoldError;
 use crate::file::Mapping;
 use crate::object::{Object, Section, Segment};
+use crate::scope::SymbolLookup;
 use crate::share_map::ShareMap;
 use crate::Env;
 
@@ -91,42 +90,36 @@ impl Manifold {
         obj_idx
     }
 
-    /// Find the given symbol across the different loaded objects. Symbols with NDX set to
-    /// [`SHN_UNDEF`](goblin::elf::section_header::SHN_UNDEF) are ignored.
+    /// Removes an object, along with its segments and sections, and drops it from the dependencies of the other
+    /// objects. Returns the removed object, if it was present.
     ///
-    /// Applies the following priority:
-    /// - Symbol with binding [`STB_LOCAL`] present in an [`SHT_STRTAB`](goblin::elf::section_header::SHT_STRTAB) section
-    ///   of the object pointed at by `local`.
-    /// - Symbol with binding [`STB_GLOBAL`] present in any [`SHT_DYNSYM`](goblin::elf::section_header::SHT_DYNSYM). Object
-    ///   are searched in the same order as they were loaded.
-    /// - Symbol with binding [`STB_WEAK`]. Same as above.
+    /// Handles to the object, its segments or its sections become invalid. Handles kept in share maps (such as
+    /// [`INITIAL_ELF_KEY`][crate::INITIAL_ELF_KEY]) are not updated: modules must check them with `objects.get`. The
+    /// object is not unmapped from memory.
+    pub fn remove_object(&mut self, handle: Handle<Object>) -> Option<Object> {
+        let obj = self.objects.remove(handle)?;
+
+        for segment in &obj.segments {
+            self.segments.remove(*segment);
+        }
+        for section in &obj.sections {
+            self.sections.remove(*section);
+        }
+        for other in &mut self.objects {
+            other.dependencies.retain(|dep| *dep != handle);
+        }
+
+        Some(obj)
+    }
+
+    /// Find the given symbol across the different loaded objects, as an unversioned reference from `local`. See
+    /// [`Manifold::resolve_symbol`] for the resolution rules.
     pub fn find_symbol<'a>(
         &'a self,
         name: &'a CStr,
         local: Handle<Object>,
     ) -> Result<(&'a Section, Sym), FoldError> {
-        // Search the local object for a `STB_LOCAL` entry.
-        if let Ok((section, sym)) = self.objects[local].find_symbol(name, self) {
-            if sym_bindings(&sym) == STB_LOCAL {
-                return Ok((section, sym));
-            }
-        }
-
-        let mut weak = Err(FoldError::SymbolNotFound(name.to_owned()));
-
-        // Go through all loaded object to find a `STB_GLOBAL`, and stores the first `STB_WEAK`
-        // in case no `STB_GLOBAL` is found.
-        for (_, obj) in self.objects.enumerate() {
-            if let Ok((section, sym)) = obj.find_dynamic_symbol(name, self) {
-                match sym_bindings(&sym) {
-                    STB_GLOBAL => return Ok((section, sym)),
-                    STB_WEAK if weak.is_err() => weak = Ok((section, sym)),
-                    _ => {}
-                }
-            }
-        }
-
-        weak
+        self.resolve_symbol(&SymbolLookup::new(name, local))
     }
 }
 
@@ -1,8 +1,15 @@
+use alloc::borrow::ToOwned;
 use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Debug;
 
 use crate::arena::Handle;
+use crate::contract::Contract;
 use crate::manifold::Manifold;
 use crate::object::{Object, Section, Segment};
+use crate::ordering::Constraints;
 
 /// A step of the linker's execution.
 ///
@@ -13,6 +20,35 @@ pub trait Module {
     /// Returns a name to display for the module. Used for logging and debugging.
     fn name(&self) -> &'static str;
 
+    /// Returns the capability tags and ordering constraints of the module, see [`Constraints`].
+    ///
+    /// They are combined with the constraints of the phase the module is registered in.
+    fn constraints(&self) -> Constraints {
+        Constraints::default()
+    }
+
+    /// Returns the [`ShareMap`][crate::ShareMap] keys required and provided by the module, see [`Contract`].
+    ///
+    /// The contracts of all the modules of a chain are checked before running it.
+    fn contract(&self) -> Contract {
+        Contract::default()
+    }
+
+    /// Called once when the phase the module is registered in starts, before any element is processed.
+    ///
+    /// Along with [`end_phase`][Module::end_phase], this allows modules to batch the work of a phase.
+    fn begin_phase(&mut self, manifold: &mut Manifold) -> Result<(), Box<dyn core::fmt::Debug>> {
+        let _ = manifold;
+        Ok(())
+    }
+
+    /// Called once when all the elements of the phase the module is registered in have been processed, including
+    /// when the remaining elements are skipped by the [`ErrorPolicy`][crate::ErrorPolicy] of the phase.
+    fn end_phase(&mut self, manifold: &mut Manifold) -> Result<(), Box<dyn core::fmt::Debug>> {
+        let _ = manifold;
+        Ok(())
+    }
+
     /// Processes an object coming from the manifold.
     ///
     /// It is ensured that `obj` successfully indexes an element in `manifold.objects`. This function will never be
@@ -88,3 +124,322 @@ pub trait Module {
         Ok(())
     }
 }
+
+// ———————————————————————————————— Elements ———————————————————————————————— //
+
+/// Element of the [`Manifold`] a module can be applied to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Element {
+    /// The whole manifold.
+    Manifold,
+    /// An object of [`Manifold::objects`].
+    Object(Handle<Object>),
+    /// A segment of [`Manifold::segments`].
+    Segment(Handle<Segment>),
+    /// A section of [`Manifold::sections`].
+    Section(Handle<Section>),
+}
+
+impl Element {
+    /// Calls the method of `module` corresponding to the element.
+    pub(crate) fn apply(
+        self,
+        module: &mut dyn Module,
+        manifold: &mut Manifold,
+    ) -> Result<(), Box<dyn core::fmt::Debug>> {
+        match self {
+            Element::Manifold => module.process_manifold(manifold),
+            Element::Object(obj) => module.process_object(obj, manifold),
+            Element::Segment(segment) => module.process_segment(segment, manifold),
+            Element::Section(section) => module.process_section(section, manifold),
+        }
+    }
+
+    /// Returns a human readable description of the element.
+    pub fn describe(self, manifold: &Manifold) -> String {
+        let path = |obj: Handle<Object>| {
+            manifold
+                .objects
+                .get(obj)
+                .map(|o| o.display_path())
+                .unwrap_or("<removed object>")
+        };
+
+        match self {
+            Element::Manifold => "manifold".to_owned(),
+            Element::Object(obj) => format!("object {:?}", path(obj)),
+            Element::Segment(segment) => {
+                let Some(obj) = manifold.segments.get(segment).map(|s| s.obj) else {
+                    return format!("removed segment {segment}");
+                };
+                let idx = manifold
+                    .objects
+                    .get(obj)
+                    .and_then(|o| o.segments.iter().position(|s| *s == segment));
+                format!(
+                    "segment #{} of object {:?}",
+                    idx.unwrap_or_default(),
+                    path(obj)
+                )
+            }
+            Element::Section(section) => {
+                let Some(obj) = manifold.sections.get(section).map(|s| s.obj) else {
+                    return format!("removed section {section}");
+                };
+                let idx = manifold
+                    .objects
+                    .get(obj)
+                    .and_then(|o| o.sections.iter().position(|s| *s == section));
+                format!(
+                    "section #{} of object {:?}",
+                    idx.unwrap_or_default(),
+                    path(obj)
+                )
+            }
+        }
+    }
+}
+
+// ———————————————————————————— Closure Modules ————————————————————————————— //
+
+/// Return type of the closures wrapped by a [`FnModule`]: either `()` for infallible closures, or a [`Result`] with
+/// any [`Debug`] error.
+pub trait ModuleResult {
+    /// Converts the value into the return type of the [`Module`] methods.
+    fn into_result(self) -> Result<(), Box<dyn Debug>>;
+}
+
+impl ModuleResult for () {
+    fn into_result(self) -> Result<(), Box<dyn Debug>> {
+        Ok(())
+    }
+}
+
+impl<E: Debug + 'static> ModuleResult for Result<(), E> {
+    fn into_result(self) -> Result<(), Box<dyn Debug>> {
+        self.map_err(|err| Box::new(err) as Box<dyn Debug>)
+    }
+}
+
+type ElementFn = Box<dyn FnMut(Element, &mut Manifold) -> Result<(), Box<dyn Debug>>>;
+
+/// A [`Module`] implemented by a closure.
+///
+/// The closure receives the [`Element`] to process. Constructors such as [`FnModule::object`] build a module from a
+/// closure handling a single kind of element, other elements being ignored.
+///
+/// ## Example
+///
+/// ```
+/// // Logs the path of every object
+/// FnModule::object("print-objects", |obj, manifold| {
+///     log::info!("{}", manifold[obj].display_path());
+/// });
+/// ```
+pub struct FnModule {
+    name: &'static str,
+    f: ElementFn,
+}
+
+impl FnModule {
+    /// Creates a module from a closure receiving any [`Element`].
+    pub fn new<R, F>(name: &'static str, mut f: F) -> Self
+    where
+        R: ModuleResult,
+        F: FnMut(Element, &mut Manifold) -> R + 'static,
+    {
+        Self {
+            name,
+            f: Box::new(move |element, manifold| f(element, manifold).into_result()),
+        }
+    }
+
+    /// Creates a module processing the manifold.
+    pub fn manifold<R, F>(name: &'static str, mut f: F) -> Self
+    where
+        R: ModuleResult,
+        F: FnMut(&mut Manifold) -> R + 'static,
+    {
+        Self::new(name, move |element, manifold| match element {
+            Element::Manifold => f(manifold).into_result(),
+            _ => Ok(()),
+        })
+    }
+
+    /// Creates a module processing objects.
+    pub fn object<R, F>(name: &'static str, mut f: F) -> Self
+    where
+        R: ModuleResult,
+        F: FnMut(Handle<Object>, &mut Manifold) -> R + 'static,
+    {
+        Self::new(name, move |element, manifold| match element {
+            Element::Object(obj) => f(obj, manifold).into_result(),
+            _ => Ok(()),
+        })
+    }
+
+    /// Creates a module processing segments.
+    pub fn segment<R, F>(name: &'static str, mut f: F) -> Self
+    where
+        R: ModuleResult,
+        F: FnMut(Handle<Segment>, &mut Manifold) -> R + 'static,
+    {
+        Self::new(name, move |element, manifold| match element {
+            Element::Segment(segment) => f(segment, manifold).into_result(),
+            _ => Ok(()),
+        })
+    }
+
+    /// Creates a module processing sections.
+    pub fn section<R, F>(name: &'static str, mut f: F) -> Self
+    where
+        R: ModuleResult,
+        F: FnMut(Handle<Section>, &mut Manifold) -> R + 'static,
+    {
+        Self::new(name, move |element, manifold| match element {
+            Element::Section(section) => f(section, manifold).into_result(),
+            _ => Ok(()),
+        })
+    }
+}
+
+impl Module for FnModule {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn process_object(
+        &mut self,
+        obj: Handle<Object>,
+        manifold: &mut Manifold,
+    ) -> Result<(), Box<dyn Debug>> {
+        (self.f)(Element::Object(obj), manifold)
+    }
+
+    fn process_segment(
+        &mut self,
+        segment: Handle<Segment>,
+        manifold: &mut Manifold,
+    ) -> Result<(), Box<dyn Debug>> {
+        (self.f)(Element::Segment(segment), manifold)
+    }
+
+    fn process_section(
+        &mut self,
+        section: Handle<Section>,
+        manifold: &mut Manifold,
+    ) -> Result<(), Box<dyn Debug>> {
+        (self.f)(Element::Section(section), manifold)
+    }
+
+    fn process_manifold(&mut self, manifold: &mut Manifold) -> Result<(), Box<dyn Debug>> {
+        (self.f)(Element::Manifold, manifold)
+    }
+}
+
+// ————————————————————————————— Module Groups —————————————————————————————— //
+
+/// Several [`Module`]s executed as a single phase.
+///
+/// Each element matched by the phase's filter is processed by all the modules of the group, in the order they were
+/// added. Processing stops at the first error. The [`Constraints`] and [`Contract`]s of the modules are combined.
+///
+/// ## Example
+///
+/// ```
+/// ModuleGroup::new("protect-and-log")
+///     .with(SysvProtect)
+///     .with(FnModule::segment("log", |segment, manifold| log::info!("{:?}", manifold[segment].flags)));
+/// ```
+pub struct ModuleGroup {
+    name: &'static str,
+    modules: Vec<Box<dyn Module>>,
+}
+
+impl ModuleGroup {
+    /// Creates an empty group.
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            modules: Vec::new(),
+        }
+    }
+
+    /// Adds a module at the end of the group.
+    pub fn with(mut self, module: impl Module + 'static) -> Self {
+        self.modules.push(Box::new(module));
+        self
+    }
+
+    /// Applies `f` to every module of the group, stopping at the first error.
+    fn each(
+        &mut self,
+        mut f: impl FnMut(&mut dyn Module) -> Result<(), Box<dyn Debug>>,
+    ) -> Result<(), Box<dyn Debug>> {
+        self.modules.iter_mut().try_for_each(|m| f(m.as_mut()))
+    }
+}
+
+impl Module for ModuleGroup {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn constraints(&self) -> Constraints {
+        self.modules
+            .iter()
+            .fold(Constraints::new(), |acc, m| acc.merge(m.constraints()))
+    }
+
+    /// Keys provided by a module of the group satisfy the requirements of the following modules.
+    fn contract(&self) -> Contract {
+        let mut contract = Contract::new();
+        for module in &self.modules {
+            let mut other = module.contract();
+            other.requires.retain(|required| {
+                !contract
+                    .provides
+                    .iter()
+                    .any(|p| p.key == required.key && p.scope == required.scope)
+            });
+            contract = contract.merge(other);
+        }
+        contract
+    }
+
+    fn begin_phase(&mut self, manifold: &mut Manifold) -> Result<(), Box<dyn Debug>> {
+        self.each(|m| m.begin_phase(manifold))
+    }
+
+    fn end_phase(&mut self, manifold: &mut Manifold) -> Result<(), Box<dyn Debug>> {
+        self.each(|m| m.end_phase(manifold))
+    }
+
+    fn process_object(
+        &mut self,
+        obj: Handle<Object>,
+        manifold: &mut Manifold,
+    ) -> Result<(), Box<dyn Debug>> {
+        self.each(|m| m.process_object(obj, manifold))
+    }
+
+    fn process_segment(
+        &mut self,
+        segment: Handle<Segment>,
+        manifold: &mut Manifold,
+    ) -> Result<(), Box<dyn Debug>> {
+        self.each(|m| m.process_segment(segment, manifold))
+    }
+
+    fn process_section(
+        &mut self,
+        section: Handle<Section>,
+        manifold: &mut Manifold,
+    ) -> Result<(), Box<dyn Debug>> {
+        self.each(|m| m.process_section(section, manifold))
+    }
+
+    fn process_manifold(&mut self, manifold: &mut Manifold) -> Result<(), Box<dyn Debug>> {
+        self.each(|m| m.process_manifold(manifold))
+    }
+}
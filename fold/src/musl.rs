@@ -8,8 +8,13 @@ use core::ptr::{read_unaligned, slice_from_raw_parts_mut};
 use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
 
 use crate::arena::{Arena, Handle};
+use crate::contract::{Contract, KeyScope};
 use crate::elf::{Object, Segment};
-use crate::sysv::loader::SYSV_LOADER_MAPPING;
+use crate::env::AuxvType;
+use crate::ordering::{Anchor, Constraints, TAG_INIT, TAG_RELOCATION, TAG_START};
+use crate::sysv::init::run_finalizers;
+use crate::sysv::loader::{SYSV_LOADER_BASE_ADDR, SYSV_LOADER_MAPPING};
+use crate::sysv::start::{build_stack, describe_program};
 use crate::{Manifold, Module, ShareMapKey};
 
 pub type Sysinfo = usize;
@@ -95,8 +100,22 @@ enum MuslLocatorError {
 
 pub struct MuslLocator;
 
-pub const MUSL_LIBC_KEY: ShareMapKey<MuslObjectIdx<Libc>> = ShareMapKey::new("musl-libc");
-pub const MUSL_SYSINFO_KEY: ShareMapKey<MuslObjectIdx<Sysinfo>> = ShareMapKey::new("musl-sysinfo");
+pub const MUSL_LIBC_KEY: ShareMapKey<MuslObjectIdx<Libc>> =
+    ShareMapKey::new("musl-locator", "libc").with_debug();
+pub const MUSL_SYSINFO_KEY: ShareMapKey<MuslObjectIdx<Sysinfo>> =
+    ShareMapKey::new("musl-locator", "sysinfo").with_debug();
+/// Canary checked by the functions compiled with a stack protector (`__stack_chk_guard`).
+pub const MUSL_STACK_GUARD_KEY: ShareMapKey<MuslObjectIdx<u64>> =
+    ShareMapKey::new("musl-locator", "stack-guard").with_debug();
+/// Environment of the program (`__environ`).
+pub const MUSL_ENVIRON_KEY: ShareMapKey<MuslObjectIdx<usize>> =
+    ShareMapKey::new("musl-locator", "environ").with_debug();
+/// Name of the program, without its directory (`__progname`).
+pub const MUSL_PROGNAME_KEY: ShareMapKey<MuslObjectIdx<usize>> =
+    ShareMapKey::new("musl-locator", "progname").with_debug();
+/// Name of the program, as given in `argv[0]` (`__progname_full`).
+pub const MUSL_PROGNAME_FULL_KEY: ShareMapKey<MuslObjectIdx<usize>> =
+    ShareMapKey::new("musl-locator", "progname-full").with_debug();
 
 #[derive(Debug, Clone)]
 pub struct MuslObjectIdx<T> {
@@ -121,13 +140,22 @@ where
         let mapping = segments[self.segment]
             .shared
             .get_mut(SYSV_LOADER_MAPPING)
-            .ok_or_else(|| Box::new(MuslLocatorError::MutObjectNotFound) as Box<dyn Debug>)?;
+            .map_err(|_| Box::new(MuslLocatorError::MutObjectNotFound) as Box<dyn Debug>)?;
 
         T::mut_from_bytes(&mut mapping.bytes_mut()[self.range.clone()])
             .map_err(|_| Box::new(MuslLocatorError::Conversion) as Box<dyn Debug>)
     }
 }
 
+/// Returns musl's `libc.so`, if it is loaded.
+pub fn find_libc(manifold: &Manifold) -> Option<Handle<Object>> {
+    manifold
+        .objects
+        .enumerate()
+        .find(|(_, o)| o.display_path().ends_with("libc.so"))
+        .map(|(obj, _)| obj)
+}
+
 fn locate_and_insert_sym<T>(
     manifold: &mut Manifold,
     obj: Handle<Object>,
@@ -136,7 +164,7 @@ fn locate_and_insert_sym<T>(
 ) where
     T: 'static,
 {
-    if let Ok((_, sym)) = manifold.find_symbol(name, obj) {
+    if let Ok((_, sym)) = manifold[obj].find_symbol(name, manifold) {
         log::trace!("Found {} at {:#x}", name.to_string_lossy(), sym.st_value,);
 
         let Some(hseg) = manifold[obj]
@@ -178,19 +206,120 @@ impl Module for MuslLocator {
         "musl-locator"
     }
 
+    fn contract(&self) -> Contract {
+        Contract::new()
+            .provides(KeyScope::Manifold, MUSL_LIBC_KEY)
+            .provides(KeyScope::Manifold, MUSL_SYSINFO_KEY)
+            .provides(KeyScope::Manifold, MUSL_STACK_GUARD_KEY)
+            .provides(KeyScope::Manifold, MUSL_ENVIRON_KEY)
+            .provides(KeyScope::Manifold, MUSL_PROGNAME_KEY)
+            .provides(KeyScope::Manifold, MUSL_PROGNAME_FULL_KEY)
+    }
+
     fn process_manifold(&mut self, manifold: &mut Manifold) -> Result<(), Box<dyn Debug>> {
-        let Some((obj, _)) = manifold
-            .objects
-            .enumerate()
-            .find(|(_, o)| o.display_path().ends_with("libc.so"))
-        else {
+        let Some(obj) = find_libc(manifold) else {
             log::warn!("Unable to find libc.so object");
             return Ok(());
         };
 
         locate_and_insert_sym(manifold, obj, c"__libc", MUSL_LIBC_KEY);
         locate_and_insert_sym(manifold, obj, c"__sysinfo", MUSL_SYSINFO_KEY);
+        locate_and_insert_sym(manifold, obj, c"__stack_chk_guard", MUSL_STACK_GUARD_KEY);
+        locate_and_insert_sym(manifold, obj, c"__environ", MUSL_ENVIRON_KEY);
+        locate_and_insert_sym(manifold, obj, c"__progname", MUSL_PROGNAME_KEY);
+        locate_and_insert_sym(manifold, obj, c"__progname_full", MUSL_PROGNAME_FULL_KEY);
 
         Ok(())
     }
 }
+
+/// Prepares musl for the initializers of the objects, which run before `__libc_start_main` when fold loads them.
+///
+/// musl runs the constructors after `__init_libc`, which sets the environment, the auxiliary vector and the name of
+/// the program: they are set here in the same way, such that `getenv` or `getauxval` work in constructors. musl's
+/// `exit` also ignores the finalizers given to the program in `rdx`, which are registered with its `atexit` instead.
+pub struct MuslInit;
+
+impl Module for MuslInit {
+    fn name(&self) -> &'static str {
+        "musl-init"
+    }
+
+    fn constraints(&self) -> Constraints {
+        Constraints::new()
+            .tag(TAG_INIT)
+            .runs_after(Anchor::tag(TAG_RELOCATION))
+            .runs_before(Anchor::tag(TAG_START))
+    }
+
+    fn contract(&self) -> Contract {
+        Contract::new()
+            .requires(KeyScope::Manifold, MUSL_LIBC_KEY)
+            .requires(KeyScope::Manifold, MUSL_ENVIRON_KEY)
+            .requires(KeyScope::Manifold, MUSL_PROGNAME_KEY)
+            .requires(KeyScope::Manifold, MUSL_PROGNAME_FULL_KEY)
+            .requires(KeyScope::Object, SYSV_LOADER_BASE_ADDR)
+    }
+
+    fn process_object(
+        &mut self,
+        obj: Handle<Object>,
+        manifold: &mut Manifold,
+    ) -> Result<(), Box<dyn Debug>> {
+        // Constructors must see the auxiliary vector of the program.
+        describe_program(obj, manifold);
+
+        // The stack of the program holds the arrays musl expects: `argv`, then `envp`, then the auxiliary vector.
+        let env = &manifold.env;
+        let stack = build_stack(env).leak();
+        let envp = &stack[env.args.len() + 2] as *const u64 as usize;
+        let auxv = &stack[env.args.len() + env.envp.len() + 3] as *const u64 as usize;
+        let argv0 = env.args.first().copied().unwrap_or_default();
+        let progname_full = argv0.as_ptr() as usize;
+        let progname = argv0
+            .to_bytes()
+            .iter()
+            .rposition(|c| *c == b'/')
+            .map_or(0, |idx| idx + 1);
+        let page_size = env.get_auxv(AuxvType::PAGESZ).unwrap_or_default() as usize;
+
+        let set = |key: ShareMapKey<MuslObjectIdx<usize>>, value, manifold: &mut Manifold| {
+            if let Ok(idx) = manifold.shared.get(key).cloned() {
+                *idx.get_mut(&mut manifold.segments)? = value;
+            }
+            Ok::<_, Box<dyn Debug>>(())
+        };
+        set(MUSL_ENVIRON_KEY, envp, manifold)?;
+        set(MUSL_PROGNAME_FULL_KEY, progname_full, manifold)?;
+        set(MUSL_PROGNAME_KEY, progname_full + progname, manifold)?;
+
+        let libc = manifold.shared.get(MUSL_LIBC_KEY)?.clone();
+        let libc = libc.get_mut(&mut manifold.segments)?;
+        libc.auxv = auxv;
+        libc.page_size = page_size;
+
+        register_finalizers(manifold)
+    }
+}
+
+/// Registers the finalizers of the objects with musl's `atexit`.
+fn register_finalizers(manifold: &Manifold) -> Result<(), Box<dyn Debug>> {
+    let Some(libc) = find_libc(manifold) else {
+        return Ok(());
+    };
+    let (_, atexit) = manifold[libc].find_dynamic_symbol(c"atexit", manifold)?;
+    let base = manifold[libc]
+        .shared
+        .get(SYSV_LOADER_BASE_ADDR)
+        .copied()
+        .unwrap_or_default();
+
+    let atexit: AtExitFn = unsafe { core::mem::transmute(base + atexit.st_value as usize) };
+    if unsafe { atexit(run_finalizers) } != 0 {
+        log::warn!("Unable to register the finalizers with atexit");
+    }
+
+    Ok(())
+}
+
+type AtExitFn = unsafe extern "C" fn(extern "C" fn()) -> i32;
@@ -4,12 +4,12 @@ use alloc::sync::Arc;
 use alloc::vec::Vec;
 use core::ffi::CStr;
 
-use goblin::elf::section_header::SHN_UNDEF;
+use goblin::elf::section_header::{SHN_LORESERVE, SHN_UNDEF, SHT_DYNAMIC, SHT_DYNSYM};
 use goblin::elf::sym::{STB_GLOBAL, STB_LOCAL, STB_WEAK};
 use goblin::elf64::sym::Sym;
 
 use crate::arena::Handle;
-use crate::elf::{sym_bindings, ElfHeader, ElfItemIterator, ProgramHeader, SectionHeader};
+use crate::elf::{sym_bindings, Dyn, ElfHeader, ElfItemIterator, ProgramHeader, SectionHeader};
 use crate::error::FoldError;
 use crate::exit::exit_error;
 use crate::file::Mapping;
@@ -149,65 +149,118 @@ impl Object {
     /// Resolves a symbol in the object.
     ///
     /// - `symbol`: Name of the symbol to resolve.
+    /// - `version`: Version required by the reference, if any. Only applies to dynamic symbols, see
+    ///   [`Object::matches_version`].
     /// - `manifold`: The current manifold.
     /// - `symbol_table_mapper`: A function to convert a [`Section`] into a [`SymbolTableSection`]. Useful to select
     ///   between dynamic and non-dynamic symbol sections.
     fn find_symbol_<'a>(
         &'a self,
         symbol: &'_ CStr,
+        version: Option<&CStr>,
         manifold: &'a Manifold,
         symbol_table_mapper: impl Fn(&'a Section) -> Result<SymbolTableSection<'a>, FoldError>,
     ) -> Result<(&'a Section, Sym), FoldError> {
         let mut weak_result = Err(FoldError::SymbolNotFound(symbol.to_owned()));
 
         // Attempt to find a LOCAL symbol in the current object
-        for section in self
+        for (handle, section) in self
             .sections
             .iter()
-            .map(|h| &manifold.sections[*h])
-            .filter_map(|s| symbol_table_mapper(s).ok())
+            .filter_map(|h| Some((*h, symbol_table_mapper(&manifold.sections[*h]).ok()?)))
         {
-            // List all entries with matching symbol name.
-            let matching_entries: Vec<(Sym, &CStr)> = section
-                .symbol_iter(manifold)
-                .filter_map(Result::ok)
-                .filter(|(_, name)| *name == symbol)
-                .collect::<Vec<_>>();
-
-            // Find an entry with LOCAL or GLOBAL visibility. If none match, use the first entry found (thus including
-            // WEAK entries as well). This implements priority between LOCAL/GLOBAL and WEAK.
-            let entry: Option<(Sym, &CStr)> = matching_entries
-                .iter()
-                .find(|(sym, _)| {
-                    let binding = sym_bindings(sym);
-
-                    (binding == STB_LOCAL || binding == STB_GLOBAL)
-                        && sym.st_shndx != SHN_UNDEF as u16
-                })
-                .or_else(|| matching_entries.first())
-                .cloned();
+            // Symbol versions only describe the entries of the dynamic symbol table.
+            let versions = (section.section.tag == SHT_DYNSYM)
+                .then(|| self.version_table(manifold))
+                .flatten();
+
+            // Go through the entries with matching symbol name and version. Use the first entry with LOCAL or GLOBAL
+            // visibility, or else the first entry found (thus including WEAK entries as well). This implements
+            // priority between LOCAL/GLOBAL and WEAK.
+            let mut entry = None;
+            for idx in self.symbol_candidates(handle, &section, symbol, manifold) {
+                let Ok((name, sym)) = section.get_symbol_and_entry(idx, manifold) else {
+                    continue;
+                };
+                if name != symbol
+                    || !versions
+                        .as_ref()
+                        .is_none_or(|v| self.matches_version(v, idx, version, manifold))
+                {
+                    continue;
+                }
+
+                let binding = sym_bindings(&sym);
+                if (binding == STB_LOCAL || binding == STB_GLOBAL) && is_defined(&sym) {
+                    entry = Some(sym);
+                    break;
+                }
+                entry.get_or_insert(sym);
+            }
 
             // If an non-weak entry is found, return it.
-            if let Some((sym, _)) = entry {
-                if sym.st_shndx != SHN_UNDEF as u16 {
-                    // Section containing the symbol.
-                    let container = manifold
-                        .sections
-                        .get(self.sections[sym.st_shndx as usize])
-                        .expect("Symbol not contained in a section");
-
-                    if sym.st_info & STB_WEAK == 0 {
-                        return Ok((container, sym));
-                    }
-
-                    weak_result = Ok((container, sym))
+            if let Some(sym) = entry.filter(is_defined) {
+                // Section containing the symbol.
+                let container = self
+                    .sections
+                    .get(sym.st_shndx as usize)
+                    .and_then(|h| manifold.sections.get(*h))
+                    .expect("Symbol not contained in a section");
+
+                if sym_bindings(&sym) != STB_WEAK {
+                    return Ok((container, sym));
                 }
+
+                weak_result = Ok((container, sym))
             }
         }
 
         weak_result
     }
 
+    /// Returns the indices of the entries of the symbol table `table` which may be named `symbol`.
+    ///
+    /// The `.gnu.hash` or `.hash` section linked to the table is used when present, otherwise all the entries are
+    /// returned.
+    fn symbol_candidates<'a>(
+        &'a self,
+        table: Handle<Section>,
+        section: &SymbolTableSection<'a>,
+        symbol: &'a CStr,
+        manifold: &'a Manifold,
+    ) -> impl Iterator<Item = usize> + 'a {
+        let hash_tables = self
+            .sections
+            .iter()
+            .map(|h| &manifold.sections[*h])
+            .filter(move |s| s.link_section == Some(table));
+        let gnu_hash = hash_tables.clone().find_map(|s| s.as_gnu_hash_table().ok());
+        let hash = gnu_hash
+            .is_none()
+            .then(|| hash_tables.clone().find_map(|s| s.as_hash_table().ok()))
+            .flatten();
+        let linear = (gnu_hash.is_none() && hash.is_none()).then(|| 0..section.len());
+
+        gnu_hash
+            .map(|h| h.lookup(symbol))
+            .into_iter()
+            .flatten()
+            .chain(hash.map(|h| h.lookup(symbol)).into_iter().flatten())
+            .chain(linear.into_iter().flatten())
+    }
+
+    /// Returns an iterator over the entries of the dynamic section (`.dynamic`) of the object, if any.
+    pub fn dynamic_entries<'a>(
+        &'a self,
+        manifold: &'a Manifold,
+    ) -> impl Iterator<Item = &'a Dyn> + 'a {
+        self.sections
+            .iter()
+            .map(|h| &manifold.sections[*h])
+            .filter(|s| s.tag == SHT_DYNAMIC)
+            .flat_map(ElfItemIterator::<Dyn>::from_section)
+    }
+
     /// Returns an iterator over all the symbols in the object as well as their string representation.
     pub fn symbols<'a>(
         &'a self,
@@ -227,18 +280,113 @@ impl Object {
         symbol: &'_ CStr,
         manifold: &'a Manifold,
     ) -> Result<(&'a Section, Sym), FoldError> {
-        self.find_symbol_(symbol, manifold, |s| s.as_symbol_table())
+        self.find_symbol_(symbol, None, manifold, |s| s.as_symbol_table())
     }
 
     /// Find the given symbol in one of the [`SHT_DYNSYM`](goblin::elf::section_header::SHT_DYNSYM) section of this
     /// object. Symbols with binding [`STB_LOCAL`] or [`STB_GLOBAL`] take priority over [`STB_WEAK`].
+    ///
+    /// The reference is unversioned: hidden versions of the symbol are ignored.
     pub fn find_dynamic_symbol<'a>(
         &'a self,
         symbol: &'_ CStr,
         manifold: &'a Manifold,
     ) -> Result<(&'a Section, Sym), FoldError> {
-        self.find_symbol_(symbol, manifold, |s| s.as_dynamic_symbol_table())
+        self.find_versioned_dynamic_symbol(symbol, None, manifold)
+    }
+
+    /// Same as [`Object::find_dynamic_symbol`], for a reference to the given `version` of the symbol.
+    pub fn find_versioned_dynamic_symbol<'a>(
+        &'a self,
+        symbol: &'_ CStr,
+        version: Option<&CStr>,
+        manifold: &'a Manifold,
+    ) -> Result<(&'a Section, Sym), FoldError> {
+        self.find_symbol_(symbol, version, manifold, |s| s.as_dynamic_symbol_table())
+    }
+
+    /// Returns the symbol version table (`.gnu.version`) of the object, if any.
+    fn version_table<'a>(&'a self, manifold: &'a Manifold) -> Option<VersionTableSection<'a>> {
+        self.sections
+            .iter()
+            .find_map(|h| manifold.sections[*h].as_version_table().ok())
+    }
+
+    /// Returns an iterator over the versions defined by the object (`.gnu.version_d`).
+    pub fn version_definitions<'a>(
+        &'a self,
+        manifold: &'a Manifold,
+    ) -> impl Iterator<Item = VersionDefinition<'a>> + 'a {
+        self.sections
+            .iter()
+            .filter_map(|h| manifold.sections[*h].as_version_definitions().ok())
+            .flat_map(move |s| s.definitions(manifold))
+    }
+
+    /// Returns an iterator over the versions the object requires from its dependencies (`.gnu.version_r`).
+    pub fn version_requirements<'a>(
+        &'a self,
+        manifold: &'a Manifold,
+    ) -> impl Iterator<Item = VersionRequirement<'a>> + 'a {
+        self.sections
+            .iter()
+            .filter_map(|h| manifold.sections[*h].as_version_requirements().ok())
+            .flat_map(move |s| s.requirements(manifold))
     }
+
+    /// Returns the name of the version referenced by the dynamic symbol at `index`, or `None` if the reference is
+    /// unversioned.
+    pub fn required_version<'a>(
+        &'a self,
+        index: usize,
+        manifold: &'a Manifold,
+    ) -> Option<&'a CStr> {
+        let version = self.version_table(manifold)?.get_version(index)? & VERSYM_VERSION;
+        if version <= VER_NDX_GLOBAL {
+            return None;
+        }
+
+        // References to the object's own symbols use the indices of its definitions.
+        self.version_requirements(manifold)
+            .find(|r| r.index == version)
+            .map(|r| r.name)
+            .or_else(|| {
+                self.version_definitions(manifold)
+                    .find(|d| d.index == version)
+                    .map(|d| d.name)
+            })
+    }
+
+    /// Returns `true` if the dynamic symbol at `index` can be bound by a reference to `version`.
+    ///
+    /// A versioned reference binds to the symbol of the same version, hidden or not, or to an unversioned symbol. An
+    /// unversioned reference binds to symbols which are not hidden, that is to the default (`sym@@VERS`) version.
+    fn matches_version(
+        &self,
+        versions: &VersionTableSection,
+        index: usize,
+        version: Option<&CStr>,
+        manifold: &Manifold,
+    ) -> bool {
+        let Some(versym) = versions.get_version(index) else {
+            return true;
+        };
+        let Some(version) = version else {
+            return versym & VERSYM_HIDDEN == 0;
+        };
+
+        let index = versym & VERSYM_VERSION;
+        index <= VER_NDX_GLOBAL
+            || self
+                .version_definitions(manifold)
+                .any(|d| d.index == index && d.name == version)
+    }
+}
+
+/// Returns `true` if the symbol is defined in one of the sections of its object. Undefined symbols, as well as absolute
+/// and common symbols which have no section, are not.
+fn is_defined(sym: &Sym) -> bool {
+    sym.st_shndx != SHN_UNDEF as u16 && sym.st_shndx < SHN_LORESERVE as u16
 }
 
 /// Returns a view of the given `bytes` as an [`ElfHeader`]. The `bytes` slice should have a length of at least
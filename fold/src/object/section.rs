@@ -3,6 +3,7 @@ use core::ffi::CStr;
 
 use goblin::elf::section_header::*;
 use goblin::elf64::section_header::SectionHeader;
+use plain::Plain;
 
 use super::Object;
 use crate::arena::Handle;
@@ -138,6 +139,31 @@ impl Section {
         SHT_SYMTAB,
         "dynamic symbol table"
     );
+    as_section!(
+        as_gnu_hash_table,
+        GnuHashSection,
+        SHT_GNU_HASH,
+        "GNU hash table"
+    );
+    as_section!(as_hash_table, HashSection, SHT_HASH, "hash table");
+    as_section!(
+        as_version_table,
+        VersionTableSection,
+        SHT_GNU_VERSYM,
+        "symbol version table"
+    );
+    as_section!(
+        as_version_definitions,
+        VersionDefinitionSection,
+        SHT_GNU_VERDEF,
+        "version definition"
+    );
+    as_section!(
+        as_version_requirements,
+        VersionRequirementSection,
+        SHT_GNU_VERNEED,
+        "version requirement"
+    );
 
     pub fn rename(&mut self, name: CString) {
         self.name = name;
@@ -173,6 +199,7 @@ impl SectionT for Section {
 // ———————————————————————————————— StringTableSection ————————————————————————————————— //
 
 /// Wrapper over a string table section (`STRTAB`), exposing extra methods to manipulate the table.
+#[derive(Clone, Copy)]
 pub struct StringTableSection<'a> {
     pub section: &'a Section,
 }
@@ -197,12 +224,26 @@ derive_sectiont!(SymbolTableSection<'_>);
 impl<'a> SymbolTableSection<'a> {
     /// Return the `DYNSYM` entry at the given index.
     pub fn get_entry(&self, index: usize) -> Result<goblin::elf::sym::sym64::Sym, FoldError> {
-        self.entry_iter()
-            .nth(index)
+        let size = core::mem::size_of::<goblin::elf::sym::sym64::Sym>();
+        self.section
+            .mapping
+            .bytes()
+            .get(index * size..(index + 1) * size)
+            .and_then(|bytes| goblin::elf::sym::sym64::Sym::from_bytes(bytes).ok())
             .copied()
             .ok_or(FoldError::OutOfBounds)
     }
 
+    /// Return the number of entries in the section.
+    pub fn len(&self) -> usize {
+        self.section.mapping.bytes().len() / core::mem::size_of::<goblin::elf::sym::sym64::Sym>()
+    }
+
+    /// Return `true` if the section has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     /// Return the symbol represented by the `DYNSYM` entry at the given index.
     pub fn get_symbol_name(
         &self,
@@ -253,3 +294,312 @@ impl<'a> SymbolTableSection<'a> {
         })
     }
 }
+
+// ————————————————————————————— GnuHashSection ————————————————————————————— //
+
+/// Wrapper over a GNU hash table section (`GNU_HASH`, `.gnu.hash`), indexing the entries of the `DYNSYM` section it is
+/// linked to.
+///
+/// A bloom filter rejects most absent symbols before the buckets are looked at.
+pub struct GnuHashSection<'a> {
+    pub section: &'a Section,
+}
+derive_sectiont!(GnuHashSection<'_>);
+
+impl<'a> GnuHashSection<'a> {
+    /// Hash function of GNU hash tables (`dl_new_hash`).
+    pub fn hash(symbol: &CStr) -> u32 {
+        symbol
+            .to_bytes()
+            .iter()
+            .fold(5381u32, |h, c| h.wrapping_mul(33).wrapping_add(*c as u32))
+    }
+
+    /// Create an iterator over the indices of the `DYNSYM` entries which may be named `symbol`. Names must still be
+    /// compared, as different names can share a hash.
+    pub fn lookup(&self, symbol: &CStr) -> impl Iterator<Item = usize> + 'a {
+        let bytes = self.section.mapping.bytes();
+        let hash = Self::hash(symbol);
+        let word = |idx: usize| read_u32(bytes, idx * 4);
+
+        let mut next = (|| {
+            let (nbuckets, symoffset) = (word(0)?, word(1)?);
+            let (bloom_size, bloom_shift) = (word(2)? as usize, word(3)?);
+            if nbuckets == 0 || bloom_size == 0 {
+                return None;
+            }
+
+            // Both bits selected by the hash must be set in the filter.
+            let bloom = read_u64(bytes, 16 + (hash as usize / 64 % bloom_size) * 8)?;
+            let mask = (1u64 << (hash % 64)) | (1u64 << ((hash >> bloom_shift) % 64));
+            if bloom & mask != mask {
+                return None;
+            }
+
+            let buckets = 4 + bloom_size * 2;
+            let chains = buckets + nbuckets as usize;
+            let symidx = word(buckets + (hash % nbuckets) as usize)?;
+            (symidx >= symoffset).then_some((chains, symoffset, symidx))
+        })();
+
+        // Chains hold the hashes of consecutive symbols, the lowest bit marking the end of the chain.
+        core::iter::from_fn(move || loop {
+            let (chains, symoffset, symidx) = next?;
+            let chain_hash = word(chains + (symidx - symoffset) as usize)?;
+            next = (chain_hash & 1 == 0).then_some((chains, symoffset, symidx + 1));
+            if chain_hash | 1 == hash | 1 {
+                return Some(symidx as usize);
+            }
+        })
+    }
+}
+
+// —————————————————————————————— HashSection ——————————————————————————————— //
+
+/// Wrapper over a System V hash table section (`HASH`, `.hash`), indexing the entries of the `DYNSYM` section it is
+/// linked to.
+pub struct HashSection<'a> {
+    pub section: &'a Section,
+}
+derive_sectiont!(HashSection<'_>);
+
+impl<'a> HashSection<'a> {
+    /// Hash function of System V hash tables (`elf_hash`).
+    pub fn hash(symbol: &CStr) -> u32 {
+        symbol.to_bytes().iter().fold(0u32, |h, c| {
+            let h = (h << 4).wrapping_add(*c as u32);
+            let high = h & 0xf000_0000;
+            (h ^ (high >> 24)) & !high
+        })
+    }
+
+    /// Create an iterator over the indices of the `DYNSYM` entries which may be named `symbol`. Names must still be
+    /// compared, as different names can share a hash.
+    pub fn lookup(&self, symbol: &CStr) -> impl Iterator<Item = usize> + 'a {
+        let bytes = self.section.mapping.bytes();
+        let word = |idx: usize| read_u32(bytes, idx * 4);
+        let hash = Self::hash(symbol);
+
+        let mut next = (|| {
+            let nbuckets = word(0)?;
+            if nbuckets == 0 {
+                return None;
+            }
+            word(2 + (hash % nbuckets) as usize)
+        })();
+
+        // Chains are indexed by symbol, index 0 (`STN_UNDEF`) ending the chain.
+        core::iter::from_fn(move || {
+            let symidx = next.filter(|idx| *idx != 0)?;
+            next = word(2 + word(0)? as usize + symidx as usize);
+            Some(symidx as usize)
+        })
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 4)?;
+    Some(u32::from_ne_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+    let bytes = bytes.get(offset..offset + 8)?;
+    Some(u64::from_ne_bytes(bytes.try_into().unwrap()))
+}
+
+// —————————————————————————— VersionTableSection ——————————————————————————— //
+
+/// Bit of a symbol version index marking a version which is not the default one (`sym@VERS` rather than `sym@@VERS`).
+pub const VERSYM_HIDDEN: u16 = 0x8000;
+/// Mask of a symbol version index selecting the index of the version.
+pub const VERSYM_VERSION: u16 = 0x7fff;
+/// Version index of unversioned global symbols.
+pub const VER_NDX_GLOBAL: u16 = 1;
+/// Flag of the version definition naming the object itself.
+pub const VER_FLG_BASE: u16 = 0x1;
+/// Flag of a version requirement which may be missing.
+pub const VER_FLG_WEAK: u16 = 0x2;
+
+/// Wrapper over a symbol version table section (`GNU_VERSYM`, `.gnu.version`), holding the version index of each
+/// entry of the `DYNSYM` section it is linked to.
+pub struct VersionTableSection<'a> {
+    pub section: &'a Section,
+}
+derive_sectiont!(VersionTableSection<'_>);
+
+impl VersionTableSection<'_> {
+    /// Return the version index of the `DYNSYM` entry at the given index, including the
+    /// [`VERSYM_HIDDEN`] bit.
+    pub fn get_version(&self, index: usize) -> Option<u16> {
+        let bytes = self.section.mapping.bytes().get(index * 2..index * 2 + 2)?;
+        Some(u16::from_ne_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+// ———————————————————————— VersionDefinitionSection ———————————————————————— //
+
+/// A version defined by an object.
+#[derive(Debug, Clone, Copy)]
+pub struct VersionDefinition<'a> {
+    /// Index of the version in the symbol version table.
+    pub index: u16,
+    /// [`VER_FLG_BASE`] marks the version naming the object itself.
+    pub flags: u16,
+    pub name: &'a CStr,
+}
+
+/// Wrapper over a version definition section (`GNU_VERDEF`, `.gnu.version_d`).
+pub struct VersionDefinitionSection<'a> {
+    pub section: &'a Section,
+}
+derive_sectiont!(VersionDefinitionSection<'_>);
+
+impl<'a> VersionDefinitionSection<'a> {
+    /// Create an iterator over the versions defined by the section. Malformed entries end the iteration.
+    pub fn definitions(
+        &self,
+        manifold: &'a Manifold,
+    ) -> impl Iterator<Item = VersionDefinition<'a>> + 'a {
+        let bytes = self.section.mapping.bytes();
+        let strings = linked_strings(self.section, manifold);
+
+        linked_entries(bytes, 0, |verdef: &Verdef| verdef.vd_next).map_while(
+            move |(offset, verdef)| {
+                // The first auxiliary entry names the version, the following ones its parents.
+                let verdaux = read_entry::<Verdaux>(bytes, offset + verdef.vd_aux as usize)?;
+                Some(VersionDefinition {
+                    index: verdef.vd_ndx,
+                    flags: verdef.vd_flags,
+                    name: strings?.get_symbol(verdaux.vda_name as usize).ok()?,
+                })
+            },
+        )
+    }
+}
+
+// ——————————————————————— VersionRequirementSection ———————————————————————— //
+
+/// A version required by an object from one of its dependencies.
+#[derive(Debug, Clone, Copy)]
+pub struct VersionRequirement<'a> {
+    /// Name of the dependency expected to define the version.
+    pub file: &'a CStr,
+    /// Index of the version in the symbol version table of the requiring object.
+    pub index: u16,
+    /// [`VER_FLG_WEAK`] marks a version which may be missing.
+    pub flags: u16,
+    pub name: &'a CStr,
+}
+
+/// Wrapper over a version requirement section (`GNU_VERNEED`, `.gnu.version_r`).
+pub struct VersionRequirementSection<'a> {
+    pub section: &'a Section,
+}
+derive_sectiont!(VersionRequirementSection<'_>);
+
+impl<'a> VersionRequirementSection<'a> {
+    /// Create an iterator over the versions required by the section. Malformed entries end the iteration.
+    pub fn requirements(
+        &self,
+        manifold: &'a Manifold,
+    ) -> impl Iterator<Item = VersionRequirement<'a>> + 'a {
+        let bytes = self.section.mapping.bytes();
+        let strings = linked_strings(self.section, manifold);
+        let string = move |offset: u32| strings?.get_symbol(offset as usize).ok();
+
+        linked_entries(bytes, 0, |verneed: &Verneed| verneed.vn_next).flat_map(
+            move |(offset, verneed)| {
+                let file = string(verneed.vn_file);
+                let start = offset + verneed.vn_aux as usize;
+                linked_entries(bytes, start, |vernaux: &Vernaux| vernaux.vna_next)
+                    .take(verneed.vn_cnt as usize)
+                    .map_while(move |(_, vernaux)| {
+                        Some(VersionRequirement {
+                            file: file?,
+                            index: vernaux.vna_other,
+                            flags: vernaux.vna_flags,
+                            name: string(vernaux.vna_name)?,
+                        })
+                    })
+            },
+        )
+    }
+}
+
+/// Returns the string table linked to a version section.
+fn linked_strings<'a>(
+    section: &'a Section,
+    manifold: &'a Manifold,
+) -> Option<StringTableSection<'a>> {
+    section
+        .get_linked_section(manifold)
+        .ok()
+        .and_then(|s| s.as_string_table().ok())
+}
+
+/// Iterates over a list of entries starting at `start`, each entry giving the offset of the next one relative to
+/// itself through `next`. The list ends on a zero offset.
+fn linked_entries<'a, T: Plain + 'a>(
+    bytes: &'a [u8],
+    start: usize,
+    next: fn(&T) -> u32,
+) -> impl Iterator<Item = (usize, &'a T)> + 'a {
+    let mut offset = Some(start);
+    core::iter::from_fn(move || {
+        let current = offset?;
+        let entry = read_entry::<T>(bytes, current)?;
+        offset = match next(entry) {
+            0 => None,
+            next => Some(current + next as usize),
+        };
+        Some((current, entry))
+    })
+}
+
+fn read_entry<T: Plain>(bytes: &[u8], offset: usize) -> Option<&T> {
+    T::from_bytes(bytes.get(offset..)?).ok()
+}
+
+/// Version definition entry (`Elf64_Verdef`).
+#[repr(C)]
+struct Verdef {
+    vd_version: u16,
+    vd_flags: u16,
+    vd_ndx: u16,
+    vd_cnt: u16,
+    vd_hash: u32,
+    vd_aux: u32,
+    vd_next: u32,
+}
+
+/// Auxiliary version definition entry (`Elf64_Verdaux`).
+#[repr(C)]
+struct Verdaux {
+    vda_name: u32,
+    vda_next: u32,
+}
+
+/// Version requirement entry (`Elf64_Verneed`).
+#[repr(C)]
+struct Verneed {
+    vn_version: u16,
+    vn_cnt: u16,
+    vn_file: u32,
+    vn_aux: u32,
+    vn_next: u32,
+}
+
+/// Auxiliary version requirement entry (`Elf64_Vernaux`).
+#[repr(C)]
+struct Vernaux {
+    vna_hash: u32,
+    vna_flags: u16,
+    vna_other: u16,
+    vna_name: u32,
+    vna_next: u32,
+}
+
+unsafe impl Plain for Verdef {}
+unsafe impl Plain for Verdaux {}
+unsafe impl Plain for Verneed {}
+unsafe impl Plain for Vernaux {}
@@ -0,0 +1,277 @@
+//! Declarative ordering of the phases of a [`Fold`][crate::Fold] chain.
+
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Tag of the phases collecting the objects composing the program.
+pub const TAG_COLLECTION: &str = "collection";
+/// Tag of the phases mapping objects into memory.
+pub const TAG_LOADING: &str = "loading";
+/// Tag of the phases setting up thread local storage.
+pub const TAG_TLS: &str = "tls";
+/// Tag of the phases applying relocations.
+pub const TAG_RELOCATION: &str = "relocation";
+/// Tag of the phases setting the final memory protections.
+pub const TAG_PROTECTION: &str = "protection";
+/// Tag of the phases running the initializers of the objects.
+pub const TAG_INIT: &str = "init";
+/// Tag of the phases transferring control to the program.
+pub const TAG_START: &str = "start";
+
+// ————————————————————————————————— Anchors ———————————————————————————————— //
+
+/// Reference to one or several phases of a chain, used to express [`Constraints`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Anchor {
+    /// The phase registered with the given name.
+    Phase(String),
+    /// All the phases with the given capability tag.
+    Tag(String),
+}
+
+impl Anchor {
+    /// Creates an anchor referencing the phase registered as `name`.
+    pub fn phase(name: impl AsRef<str>) -> Self {
+        Self::Phase(name.as_ref().to_owned())
+    }
+
+    /// Creates an anchor referencing all phases with the capability `tag`.
+    pub fn tag(tag: impl AsRef<str>) -> Self {
+        Self::Tag(tag.as_ref().to_owned())
+    }
+
+    fn matches(&self, name: &str, constraints: &Constraints) -> bool {
+        match self {
+            Anchor::Phase(phase) => phase == name,
+            Anchor::Tag(tag) => constraints.tags.iter().any(|t| t == tag),
+        }
+    }
+}
+
+impl fmt::Display for Anchor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Anchor::Phase(name) => write!(f, "phase \"{name}\""),
+            Anchor::Tag(tag) => write!(f, "tag \"{tag}\""),
+        }
+    }
+}
+
+// ——————————————————————————————— Constraints —————————————————————————————— //
+
+/// Ordering constraints and capability tags of a phase.
+///
+/// Constraints can be declared by a [`Module`][crate::Module] with
+/// [`Module::constraints`][crate::Module::constraints], or added to a phase with
+/// [`ModuleHandle::runs_after`][crate::ModuleHandle::runs_after] and similar methods. Before executing the chain,
+/// [`Fold::run`][crate::Fold::run] reorders the phases to satisfy all constraints, keeping the registration order
+/// otherwise.
+///
+/// ## Example
+///
+/// ```
+/// // Runs after all relocations are applied, but before the memory is protected.
+/// Constraints::new()
+///     .runs_after(Anchor::tag(TAG_RELOCATION))
+///     .runs_before(Anchor::tag(TAG_PROTECTION));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Constraints {
+    /// Capability tags of the phase.
+    pub tags: Vec<String>,
+    /// Anchors of the phases that must be executed before this one.
+    pub runs_after: Vec<Anchor>,
+    /// Anchors of the phases that must be executed after this one.
+    pub runs_before: Vec<Anchor>,
+}
+
+impl Constraints {
+    /// Creates an empty set of constraints.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a capability tag.
+    pub fn tag(mut self, tag: impl AsRef<str>) -> Self {
+        self.tags.push(tag.as_ref().to_owned());
+        self
+    }
+
+    /// Requires the phase to run after the phases matched by `anchor`.
+    pub fn runs_after(mut self, anchor: Anchor) -> Self {
+        self.runs_after.push(anchor);
+        self
+    }
+
+    /// Requires the phase to run before the phases matched by `anchor`.
+    pub fn runs_before(mut self, anchor: Anchor) -> Self {
+        self.runs_before.push(anchor);
+        self
+    }
+
+    /// Adds the tags and constraints of `other` to `self`.
+    pub fn merge(mut self, other: Constraints) -> Self {
+        self.tags.extend(other.tags);
+        self.runs_after.extend(other.runs_after);
+        self.runs_before.extend(other.runs_before);
+        self
+    }
+}
+
+// ————————————————————————————————— Sorting ———————————————————————————————— //
+
+/// Error detected while ordering the phases of a chain.
+#[derive(Debug)]
+pub enum OrderingError {
+    /// A constraint of `phase` references an anchor matching no other phase.
+    Unsatisfied { phase: String, anchor: Anchor },
+    /// The constraints of these phases form a cycle.
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for OrderingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderingError::Unsatisfied { phase, anchor } => write!(
+                f,
+                "phase \"{phase}\" has a constraint on {anchor}, but no such phase is registered"
+            ),
+            OrderingError::Cycle(phases) => {
+                write!(f, "ordering constraints form a cycle: ")?;
+                for phase in phases {
+                    write!(f, "\"{phase}\" -> ")?;
+                }
+                match phases.first() {
+                    Some(first) => write!(f, "\"{first}\""),
+                    None => write!(f, "..."),
+                }
+            }
+        }
+    }
+}
+
+/// Computes an execution order of the phases satisfying all constraints, as a list of indexes into `phases`.
+///
+/// The order is stable: phases that are not constrained relative to each other keep their relative position.
+pub(crate) fn sort(phases: &[(&str, Constraints)]) -> Result<Vec<usize>, Vec<OrderingError>> {
+    let n = phases.len();
+    let mut errors = Vec::new();
+
+    // `successors[i]` contains the phases that must run after phase `i`.
+    let mut successors: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (idx, (name, constraints)) in phases.iter().enumerate() {
+        let anchors = constraints
+            .runs_after
+            .iter()
+            .map(|a| (a, true))
+            .chain(constraints.runs_before.iter().map(|a| (a, false)));
+
+        for (anchor, after) in anchors {
+            let matching = phases
+                .iter()
+                .enumerate()
+                .filter(|(other, (name, constraints))| {
+                    *other != idx && anchor.matches(name, constraints)
+                })
+                .map(|(other, _)| other)
+                .collect::<Vec<_>>();
+
+            if matching.is_empty() {
+                errors.push(OrderingError::Unsatisfied {
+                    phase: (*name).to_owned(),
+                    anchor: anchor.clone(),
+                });
+            }
+
+            for other in matching {
+                if after {
+                    successors[other].push(idx);
+                } else {
+                    successors[idx].push(other);
+                }
+            }
+        }
+    }
+
+    let mut in_degree = vec![0; n];
+    for succ in successors.iter().flatten() {
+        in_degree[*succ] += 1;
+    }
+
+    // A phase that must run before another one is scheduled no later than that other phase would be.
+    let priority = (0..n)
+        .map(|idx| reachable(&successors, idx).min().unwrap_or(idx))
+        .collect::<Vec<_>>();
+
+    // Kahn's algorithm, picking the ready phase with the highest priority, then the first registered one.
+    let mut order = Vec::with_capacity(n);
+    let mut done = vec![false; n];
+    while let Some(next) = (0..n)
+        .filter(|i| !done[*i] && in_degree[*i] == 0)
+        .min_by_key(|i| (priority[*i], *i))
+    {
+        done[next] = true;
+        order.push(next);
+        for succ in &successors[next] {
+            in_degree[*succ] -= 1;
+        }
+    }
+
+    if order.len() != n {
+        errors.push(OrderingError::Cycle(find_cycle(&successors, &done, phases)));
+    }
+
+    if errors.is_empty() {
+        Ok(order)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Returns an iterator over the phases reachable from `start` (including itself).
+fn reachable(successors: &[Vec<usize>], start: usize) -> impl Iterator<Item = usize> {
+    let mut visited = vec![false; successors.len()];
+    let mut stack = vec![start];
+    while let Some(node) = stack.pop() {
+        if !visited[node] {
+            visited[node] = true;
+            stack.extend(successors[node].iter().copied());
+        }
+    }
+
+    visited
+        .into_iter()
+        .enumerate()
+        .filter(|(_, v)| *v)
+        .map(|(idx, _)| idx)
+}
+
+/// Returns the names of the phases forming a cycle among the phases not yet ordered.
+fn find_cycle(
+    successors: &[Vec<usize>],
+    done: &[bool],
+    phases: &[(&str, Constraints)],
+) -> Vec<String> {
+    // Every remaining phase has a remaining predecessor: walking backward eventually loops.
+    let predecessor =
+        |node: usize| (0..successors.len()).find(|p| !done[*p] && successors[*p].contains(&node));
+
+    let mut path: Vec<usize> = Vec::new();
+    let mut node = (0..done.len()).find(|i| !done[*i]);
+    while let Some(current) = node {
+        if let Some(start) = path.iter().position(|p| *p == current) {
+            return path[start..]
+                .iter()
+                .rev()
+                .map(|p| phases[*p].0.to_owned())
+                .collect();
+        }
+        path.push(current);
+        node = predecessor(current);
+    }
+
+    Vec::new()
+}
@@ -0,0 +1,100 @@
+//! Error handling of the [`Fold`][crate::Fold] driver.
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::module::Module;
+use crate::share_map::ShareMapKey;
+
+/// Errors recovered by the driver during [`Fold::run`][crate::Fold::run].
+pub const FOLD_ERROR_REPORT_KEY: ShareMapKey<ErrorReport> =
+    ShareMapKey::new("fold", "error-report");
+
+// ————————————————————————————— Error Policies ————————————————————————————— //
+
+/// Behavior of the [`Fold`][crate::Fold] driver when the module of a phase returns an error.
+///
+/// Each phase is registered with [`ErrorPolicy::Abort`], which can be changed using
+/// [`ModuleHandle::on_error`][crate::ModuleHandle::on_error]. Recovered errors are collected in an [`ErrorReport`].
+#[derive(Default)]
+pub enum ErrorPolicy {
+    /// Stops the linker.
+    #[default]
+    Abort,
+    /// Continues with the next element of the phase.
+    SkipItem,
+    /// Skips the remaining elements of the phase and continues with the next phase.
+    SkipPhase,
+    /// Processes the failing element with another module. The linker stops if the fallback module fails as well.
+    Fallback(Box<dyn Module>),
+}
+
+impl fmt::Debug for ErrorPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Abort => write!(f, "Abort"),
+            Self::SkipItem => write!(f, "SkipItem"),
+            Self::SkipPhase => write!(f, "SkipPhase"),
+            Self::Fallback(module) => write!(f, "Fallback({})", module.name()),
+        }
+    }
+}
+
+// —————————————————————————————— Error Report —————————————————————————————— //
+
+/// An error returned by the module of a phase.
+pub struct PhaseError {
+    /// Name of the phase.
+    pub phase: String,
+    /// Name of the module that returned the error.
+    pub module: &'static str,
+    /// Description of the element that was processed.
+    pub element: String,
+    /// Error returned by the module.
+    pub error: Box<dyn fmt::Debug>,
+}
+
+/// Errors returned by modules during the execution of a chain.
+///
+/// The report is available in the [`Manifold::shared`][crate::Manifold::shared] map under
+/// [`FOLD_ERROR_REPORT_KEY`], and is logged at the end of [`Fold::run`][crate::Fold::run].
+#[derive(Default)]
+pub struct ErrorReport {
+    errors: Vec<PhaseError>,
+}
+
+impl ErrorReport {
+    /// Returns `true` if no error were reported.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns the reported errors, in the order they occurred.
+    pub fn errors(&self) -> &[PhaseError] {
+        &self.errors
+    }
+
+    pub(crate) fn push(&mut self, error: PhaseError) {
+        self.errors.push(error);
+    }
+
+    /// Logs the content of the report, if any.
+    pub fn log(&self) {
+        if self.is_empty() {
+            return;
+        }
+
+        log::warn!("{} error(s) reported by the modules:", self.errors.len());
+        for error in &self.errors {
+            log::warn!(
+                "- [{}] {} with module {}: {:?}",
+                error.phase,
+                error.element,
+                error.module,
+                error.error
+            );
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! # Symbol resolution
+//!
+//! Resolution of symbol references across the loaded objects, following the System V rules.
+
+use alloc::borrow::ToOwned;
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::vec::Vec;
+use core::ffi::CStr;
+
+use goblin::elf::dynamic::{DF_1_GROUP, DF_SYMBOLIC, DT_FLAGS, DT_FLAGS_1, DT_SYMBOLIC};
+use goblin::elf::sym::{STB_LOCAL, STT_TLS, STV_HIDDEN, STV_INTERNAL, STV_PROTECTED};
+use goblin::elf64::sym::Sym;
+
+use crate::arena::Handle;
+use crate::driver::INITIAL_ELF_KEY;
+use crate::elf::{sym_bindings, sym_type, sym_visibility};
+use crate::error::FoldError;
+use crate::manifold::Manifold;
+use crate::object::{Object, Section};
+
+// ————————————————————————————————— Lookup ————————————————————————————————— //
+
+/// A reference to a symbol, resolved with [`Manifold::resolve_symbol`].
+#[derive(Debug, Clone, Copy)]
+pub struct SymbolLookup<'a> {
+    /// Name of the symbol.
+    pub name: &'a CStr,
+    /// Version required by the reference, if any.
+    pub version: Option<&'a CStr>,
+    /// Object containing the reference.
+    pub requester: Handle<Object>,
+    /// Whether the reference is to a thread-local (`STT_TLS`) symbol.
+    pub tls: bool,
+    /// Whether the requester is excluded from the scope, as for copy relocations.
+    pub copy: bool,
+}
+
+impl<'a> SymbolLookup<'a> {
+    /// Creates an unversioned lookup of the symbol `name`, referenced by `requester`.
+    pub fn new(name: &'a CStr, requester: Handle<Object>) -> Self {
+        Self {
+            name,
+            version: None,
+            requester,
+            tls: false,
+            copy: false,
+        }
+    }
+
+    /// Creates the lookup of the dynamic symbol at `index` in `requester`, such as the symbol of a relocation. The
+    /// version and type of the reference are read from the requester.
+    pub fn reference(
+        requester: Handle<Object>,
+        index: usize,
+        manifold: &'a Manifold,
+    ) -> Result<Self, FoldError> {
+        let obj = &manifold[requester];
+        let (name, sym) = obj
+            .sections
+            .iter()
+            .find_map(|h| manifold[*h].as_dynamic_symbol_table().ok())
+            .ok_or(FoldError::OutOfBounds)?
+            .get_symbol_and_entry(index, manifold)?;
+
+        Ok(Self::new(name, requester)
+            .version(obj.required_version(index, manifold))
+            .tls(sym_type(&sym) == STT_TLS))
+    }
+
+    /// Sets the version required by the reference.
+    pub fn version(mut self, version: Option<&'a CStr>) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets whether the reference is to a thread-local symbol.
+    pub fn tls(mut self, tls: bool) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    /// Excludes the requester from the scope, such that the definition copied by a copy relocation is found.
+    pub fn copy(mut self) -> Self {
+        self.copy = true;
+        self
+    }
+}
+
+// ————————————————————————————————— Scopes ————————————————————————————————— //
+
+/// Flags of the dynamic section of an object altering the resolution of its references.
+#[derive(Debug, Default, Clone, Copy)]
+struct ScopeFlags {
+    /// `DT_SYMBOLIC` or `DF_SYMBOLIC`: the object's own definitions come first.
+    symbolic: bool,
+    /// `DF_1_GROUP`: references are only resolved within the object and its dependencies.
+    group: bool,
+}
+
+impl ScopeFlags {
+    fn of(obj: &Object, manifold: &Manifold) -> Self {
+        obj.dynamic_entries(manifold)
+            .fold(Self::default(), |flags, entry| match entry.d_tag {
+                DT_SYMBOLIC => Self {
+                    symbolic: true,
+                    ..flags
+                },
+                DT_FLAGS => Self {
+                    symbolic: flags.symbolic || entry.d_val & DF_SYMBOLIC != 0,
+                    ..flags
+                },
+                DT_FLAGS_1 => Self {
+                    group: flags.group || entry.d_val & DF_1_GROUP != 0,
+                    ..flags
+                },
+                _ => flags,
+            })
+    }
+}
+
+/// The objects searched for definitions, computed once with [`Manifold::scope`] and shared by the lookups of a
+/// phase.
+#[derive(Debug, Clone, Default)]
+pub struct Scope {
+    /// The global scope, in breadth-first order.
+    global: Vec<Handle<Object>>,
+    /// Objects whose flags alter the resolution of their references.
+    flags: BTreeMap<Handle<Object>, ScopeFlags>,
+    /// Scopes of the objects linked with `DF_1_GROUP`: the object and its dependencies, in breadth-first order.
+    groups: BTreeMap<Handle<Object>, Vec<Handle<Object>>>,
+}
+
+impl Scope {
+    /// Returns the global scope.
+    pub fn global(&self) -> &[Handle<Object>] {
+        &self.global
+    }
+
+    fn flags(&self, obj: Handle<Object>) -> ScopeFlags {
+        self.flags.get(&obj).copied().unwrap_or_default()
+    }
+
+    /// Returns the objects searched for the references of `requester`.
+    fn objects(&self, requester: Handle<Object>) -> &[Handle<Object>] {
+        self.groups.get(&requester).unwrap_or(&self.global)
+    }
+}
+
+impl Manifold {
+    /// Computes the [`Scope`] of the loaded objects, to be passed to [`Manifold::resolve_symbol_in`].
+    pub fn scope(&self) -> Scope {
+        let mut scope = Scope {
+            global: self.global_scope(),
+            ..Default::default()
+        };
+        for (obj, object) in self.objects.enumerate() {
+            let flags = ScopeFlags::of(object, self);
+            if flags.group {
+                scope.groups.insert(obj, self.dependency_scope(obj));
+            }
+            if flags.symbolic || flags.group {
+                scope.flags.insert(obj, flags);
+            }
+        }
+        scope
+    }
+
+    /// Returns the global scope: the main object followed by its dependencies, in breadth-first order.
+    ///
+    /// Objects outside of the dependency tree of the main object, such as ones added by custom modules, come last in
+    /// load order. Without a main object, the first loaded object is used instead.
+    pub fn global_scope(&self) -> Vec<Handle<Object>> {
+        let root = self
+            .shared
+            .get(INITIAL_ELF_KEY)
+            .ok()
+            .copied()
+            .or_else(|| self.objects.enumerate().map(|(h, _)| h).next());
+
+        let mut visited = BTreeSet::new();
+        let mut scope = root
+            .map(|root| self.dependency_scope_(root, &mut visited))
+            .unwrap_or_default();
+        for (handle, _) in self.objects.enumerate() {
+            if visited.insert(handle) {
+                scope.push(handle);
+            }
+        }
+        scope
+    }
+
+    /// Returns `root` followed by its dependencies, in breadth-first order.
+    fn dependency_scope(&self, root: Handle<Object>) -> Vec<Handle<Object>> {
+        self.dependency_scope_(root, &mut BTreeSet::new())
+    }
+
+    fn dependency_scope_(
+        &self,
+        root: Handle<Object>,
+        visited: &mut BTreeSet<Handle<Object>>,
+    ) -> Vec<Handle<Object>> {
+        let mut scope = Vec::new();
+        let mut queue = VecDeque::from([root]);
+        while let Some(obj) = queue.pop_front() {
+            // The root may have been removed from the manifold.
+            let Some(object) = self.objects.get(obj) else {
+                continue;
+            };
+            if !visited.insert(obj) {
+                continue;
+            }
+            scope.push(obj);
+            queue.extend(object.dependencies.iter().copied());
+        }
+        scope
+    }
+
+    /// Resolves a symbol reference, and returns the section containing the definition and its symbol entry.
+    ///
+    /// The [`Scope`] is computed for this lookup only: use [`Manifold::resolve_symbol_in`] to resolve many references.
+    pub fn resolve_symbol<'a>(
+        &'a self,
+        lookup: &SymbolLookup,
+    ) -> Result<(&'a Section, Sym), FoldError> {
+        self.resolve_symbol_in(&self.scope(), lookup)
+    }
+
+    /// Resolves a symbol reference within `scope`, and returns the section containing the definition and its symbol
+    /// entry. The lookup does not allocate unless the symbol is not found.
+    ///
+    /// Definitions are searched in the following order:
+    /// - The requester itself, if it was linked with `DT_SYMBOLIC`, or if it defines the symbol with protected
+    ///   visibility (`STV_PROTECTED`).
+    /// - The objects of the [global scope](Manifold::global_scope), or of the requester and its dependencies if it
+    ///   was linked with `DF_1_GROUP`, in breadth-first order.
+    ///
+    /// The first definition found wins, whether it is `STB_GLOBAL` or `STB_WEAK`. Within an object, `STB_GLOBAL`
+    /// definitions take priority over `STB_WEAK` ones. Only definitions which are exported (not `STB_LOCAL`, nor
+    /// `STV_HIDDEN` or `STV_INTERNAL`), match the required version (see
+    /// [`Object::find_versioned_dynamic_symbol`]), and are thread-local if and only if the reference is, are
+    /// considered. For copy relocations, the requester is excluded from the search.
+    pub fn resolve_symbol_in<'a>(
+        &'a self,
+        scope: &Scope,
+        lookup: &SymbolLookup,
+    ) -> Result<(&'a Section, Sym), FoldError> {
+        let flags = scope.flags(lookup.requester);
+
+        if !lookup.copy {
+            if let Some((section, sym)) = self.definition(lookup.requester, lookup) {
+                if flags.symbolic || sym_visibility(&sym) == STV_PROTECTED {
+                    return Ok((section, sym));
+                }
+            }
+        }
+
+        scope
+            .objects(lookup.requester)
+            .iter()
+            .filter(|obj| !(lookup.copy && **obj == lookup.requester))
+            .find_map(|obj| self.definition(*obj, lookup))
+            .ok_or_else(|| FoldError::SymbolNotFound(lookup.name.to_owned()))
+    }
+
+    /// Returns the definition of the looked up symbol exported by `obj`, if any.
+    fn definition<'a>(
+        &'a self,
+        obj: Handle<Object>,
+        lookup: &SymbolLookup,
+    ) -> Option<(&'a Section, Sym)> {
+        let (section, sym) = self[obj]
+            .find_versioned_dynamic_symbol(lookup.name, lookup.version, self)
+            .ok()?;
+
+        let exported = sym_bindings(&sym) != STB_LOCAL
+            && !matches!(sym_visibility(&sym), STV_HIDDEN | STV_INTERNAL);
+        let tls = sym_type(&sym) == STT_TLS;
+        (exported && tls == lookup.tls).then_some((section, sym))
+    }
+}
@@ -1,35 +1,152 @@
 use alloc::boxed::Box;
 use alloc::collections::btree_map::Entry;
 use alloc::collections::BTreeMap;
-use core::any::Any;
+use core::any::{type_name, Any, TypeId};
+use core::fmt;
 use core::marker::PhantomData;
 
-#[derive(Debug, Clone, Copy)]
+/// Renders a value stored in a [`ShareMap`] with its [`Debug`][fmt::Debug] implementation.
+type DebugFn = fn(&dyn Any, &mut fmt::Formatter<'_>) -> fmt::Result;
+
 /// A key into a [`ShareMap`].
 ///
 /// The type `T` is the type of the corresponding value in the [`ShareMap`]. As a convention, [`ShareMapKey`]s should be
 /// exposed as global constants to allow easy communication between modules.
+///
+/// Keys are namespaced by the module owning them, such that two modules picking the same key string do not overwrite
+/// each other. Keys created with [`ShareMapKey::with_debug`] also render their value in diagnostics, such as
+/// [`Manifold::inspect`][crate::Manifold::inspect].
+///
+/// ## Example
+///
+/// ```
+/// pub const MY_MODULE_RESULT_KEY: ShareMapKey<usize> = ShareMapKey::new("my-module", "result").with_debug();
+/// ```
 pub struct ShareMapKey<T> {
+    /// Name of the module owning the key.
+    pub namespace: &'static str,
+    /// Name of the key within its namespace.
     pub key: &'static str,
+    debug: Option<DebugFn>,
     _marker: PhantomData<T>,
 }
 
 impl<T> ShareMapKey<T> {
-    pub const fn new(key: &'static str) -> Self {
+    /// Creates the key `key` in the `namespace` of the owning module.
+    pub const fn new(namespace: &'static str, key: &'static str) -> Self {
         Self {
+            namespace,
             key,
+            debug: None,
             _marker: PhantomData,
         }
     }
+
+    /// Returns the fully qualified name of the key.
+    pub const fn name(&self) -> KeyName {
+        KeyName {
+            namespace: self.namespace,
+            key: self.key,
+        }
+    }
 }
 
-#[derive(Debug, Default)]
+impl<T: fmt::Debug + 'static> ShareMapKey<T> {
+    /// Renders the values stored with this key using their [`Debug`][fmt::Debug] implementation.
+    pub const fn with_debug(mut self) -> Self {
+        self.debug = Some(debug_value::<T>);
+        self
+    }
+}
+
+fn debug_value<T: fmt::Debug + 'static>(
+    value: &dyn Any,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    match value.downcast_ref::<T>() {
+        Some(value) => value.fmt(f),
+        None => f.write_str("<?>"),
+    }
+}
+
+impl<T> Clone for ShareMapKey<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ShareMapKey<T> {}
+
+impl<T> fmt::Debug for ShareMapKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ShareMapKey({}: {})", self.name(), type_name::<T>())
+    }
+}
+
+/// Fully qualified name of a [`ShareMapKey`], displayed as `namespace::key`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct KeyName {
+    pub namespace: &'static str,
+    pub key: &'static str,
+}
+
+impl fmt::Display for KeyName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}", self.namespace, self.key)
+    }
+}
+
+// ————————————————————————————————— Errors ————————————————————————————————— //
+
+/// Error returned when retrieving a value from a [`ShareMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareMapError {
+    /// No value is stored with the key.
+    Missing(KeyName),
+    /// A value is stored with the key, but with another type than the one of the key.
+    TypeMismatch {
+        key: KeyName,
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+impl fmt::Display for ShareMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareMapError::Missing(key) => write!(f, "no value for key {key}"),
+            ShareMapError::TypeMismatch {
+                key,
+                expected,
+                found,
+            } => write!(f, "key {key} holds a {found}, expected a {expected}"),
+        }
+    }
+}
+
+impl From<ShareMapError> for Box<dyn fmt::Debug> {
+    fn from(value: ShareMapError) -> Self {
+        Box::new(value)
+    }
+}
+
+// ———————————————————————————————— ShareMap ———————————————————————————————— //
+
+/// A value stored in a [`ShareMap`], along with the information needed to describe it.
+struct Value {
+    value: Box<dyn Any>,
+    type_name: &'static str,
+    debug: Option<DebugFn>,
+}
+
+#[derive(Default)]
 /// Shared memory to allow communication between [`Module`][crate::Module]s.
 ///
 /// Entries are stored as a map between a [`ShareMapKey`] and any type. The [`ShareMapKey`]'s generic type must match
-/// the type of the entry's value, ensuring typesafety.
+/// the type of the entry's value, ensuring typesafety: retrieving a value with a key of another type returns
+/// [`ShareMapError::TypeMismatch`].
 pub struct ShareMap {
-    map: BTreeMap<&'static str, Box<dyn Any>>,
+    map: BTreeMap<KeyName, Value>,
 }
 
 impl ShareMap {
@@ -37,26 +154,72 @@ impl ShareMap {
         Default::default()
     }
 
-    /// Insert a new value in the map, overwriting any values registered with the same key string.
+    /// Insert a new value in the map, overwriting any values registered with the same key. A warning is emitted if
+    /// the overwritten value has another type.
     pub fn insert<T: 'static>(&mut self, key: ShareMapKey<T>, value: T) {
-        self.map.insert(key.key, Box::new(value));
+        let previous = self.map.insert(key.name(), Value::new(key, value));
+        if let Some(previous) = previous.filter(|p| !p.value.is::<T>()) {
+            log::warn!(
+                "Share map key {} overwritten: {} replaced by {}",
+                key.name(),
+                previous.type_name,
+                type_name::<T>()
+            );
+        }
     }
 
     /// Retreives a value from the map. The type `T` of the `key` must match the type of the value in the map.
-    pub fn get<T: 'static>(&self, key: ShareMapKey<T>) -> Option<&T> {
-        self.map.get(key.key).and_then(|v| v.downcast_ref())
+    pub fn get<T: 'static>(&self, key: ShareMapKey<T>) -> Result<&T, ShareMapError> {
+        let value = self
+            .map
+            .get(&key.name())
+            .ok_or(ShareMapError::Missing(key.name()))?;
+        value.value.downcast_ref().ok_or(value.mismatch(key))
     }
 
     /// Retreives a value from the map. The type `T` of the `key` must match the type of the value in the map.
-    pub fn get_mut<T: 'static>(&mut self, key: ShareMapKey<T>) -> Option<&mut T> {
-        self.map.get_mut(key.key).and_then(|v| v.downcast_mut())
+    pub fn get_mut<T: 'static>(&mut self, key: ShareMapKey<T>) -> Result<&mut T, ShareMapError> {
+        let value = self
+            .map
+            .get_mut(&key.name())
+            .ok_or(ShareMapError::Missing(key.name()))?;
+        let mismatch = value.mismatch(key);
+        value.value.downcast_mut().ok_or(mismatch)
     }
 
-    /// Retreives a value from the map. The type `T` of the `key` must match the type of the value in the map.
-    pub fn take<T: 'static>(&mut self, key: ShareMapKey<T>) -> Option<T> {
+    /// Removes a value from the map. The type `T` of the `key` must match the type of the value in the map, otherwise
+    /// the value is left in the map.
+    pub fn take<T: 'static>(&mut self, key: ShareMapKey<T>) -> Result<T, ShareMapError> {
+        let Entry::Occupied(entry) = self.map.entry(key.name()) else {
+            return Err(ShareMapError::Missing(key.name()));
+        };
+        if !entry.get().value.is::<T>() {
+            return Err(entry.get().mismatch(key));
+        }
+
+        Ok(*entry.remove().value.downcast().unwrap())
+    }
+
+    /// Returns `true` if a value is stored with `key`, regardless of its type.
+    pub fn contains<T: 'static>(&self, key: ShareMapKey<T>) -> bool {
+        self.map.contains_key(&key.name())
+    }
+
+    /// Returns an iterator over the keys present in the map, in alphabetical order.
+    pub fn keys(&self) -> impl Iterator<Item = KeyName> + '_ {
+        self.map.keys().copied()
+    }
+
+    /// Returns an iterator over the entries of the map, in alphabetical order of their keys.
+    pub fn entries(&self) -> impl Iterator<Item = ShareMapEntry<'_>> {
         self.map
-            .remove(key.key)
-            .and_then(|v| v.downcast().map(|v| *v).ok())
+            .iter()
+            .map(|(key, value)| ShareMapEntry { key: *key, value })
+    }
+
+    /// Returns the [`TypeId`] of the value stored with the given key, if any.
+    pub(crate) fn type_id_of(&self, key: KeyName) -> Option<TypeId> {
+        self.map.get(&key).map(|v| (*v.value).type_id())
     }
 
     pub fn insert_or_update<T: 'static, A: FnOnce() -> T, U: FnOnce(&mut T)>(
@@ -65,12 +228,12 @@ impl ShareMap {
         absent: A,
         update: U,
     ) -> bool {
-        match self.map.entry(key.key) {
+        match self.map.entry(key.name()) {
             Entry::Vacant(vacant_entry) => {
-                vacant_entry.insert(Box::new(absent()));
+                vacant_entry.insert(Value::new(key, absent()));
             }
             Entry::Occupied(mut occupied_entry) => {
-                let Some(entry) = occupied_entry.get_mut().downcast_mut() else {
+                let Some(entry) = occupied_entry.get_mut().value.downcast_mut() else {
                     return false;
                 };
                 update(entry);
@@ -80,3 +243,77 @@ impl ShareMap {
         true
     }
 }
+
+impl Value {
+    fn new<T: 'static>(key: ShareMapKey<T>, value: T) -> Self {
+        Self {
+            value: Box::new(value),
+            type_name: type_name::<T>(),
+            debug: key.debug,
+        }
+    }
+
+    fn mismatch<T>(&self, key: ShareMapKey<T>) -> ShareMapError {
+        ShareMapError::TypeMismatch {
+            key: key.name(),
+            expected: type_name::<T>(),
+            found: self.type_name,
+        }
+    }
+}
+
+impl fmt::Debug for ShareMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.entries().map(|entry| (entry.key, entry)))
+            .finish()
+    }
+}
+
+// ————————————————————————————————— Entries ———————————————————————————————— //
+
+/// An entry of a [`ShareMap`], as listed by [`ShareMap::entries`].
+///
+/// Its [`Display`][fmt::Display] implementation shows the type of the value, and the value itself if its key was
+/// created with [`ShareMapKey::with_debug`].
+#[derive(Clone, Copy)]
+pub struct ShareMapEntry<'a> {
+    key: KeyName,
+    value: &'a Value,
+}
+
+impl ShareMapEntry<'_> {
+    /// Returns the key of the entry.
+    pub fn key(&self) -> KeyName {
+        self.key
+    }
+
+    /// Returns the name of the Rust type of the value.
+    pub fn type_name(&self) -> &'static str {
+        self.value.type_name
+    }
+
+    /// Returns `true` if the value can be rendered with its [`Debug`][fmt::Debug] implementation.
+    pub fn has_debug(&self) -> bool {
+        self.value.debug.is_some()
+    }
+}
+
+impl fmt::Display for ShareMapEntry<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.value.type_name)?;
+        if self.value.debug.is_some() {
+            write!(f, " = {self:?}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ShareMapEntry<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value.debug {
+            Some(debug) => debug(&*self.value.value, f),
+            None => write!(f, "<{}>", self.value.type_name),
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! Timing and statistics of the execution of a [`Fold`][crate::Fold] chain.
+
+use alloc::collections::BTreeMap;
+use alloc::ffi::CString;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use goblin::elf::header::EM_X86_64;
+use goblin::elf::reloc::r_to_str;
+use rustix::fs::{self, Mode, OFlags};
+use rustix::io;
+use rustix::time::{clock_gettime, ClockId};
+
+use crate::println;
+use crate::share_map::ShareMapKey;
+
+/// Statistics collected during [`Fold::run`][crate::Fold::run], present only if statistics are enabled.
+pub const FOLD_STATS_KEY: ShareMapKey<Stats> = ShareMapKey::new("fold", "stats");
+
+/// Returns the current value of the monotonic clock, in nanoseconds.
+pub(crate) fn now() -> u64 {
+    let time = clock_gettime(ClockId::Monotonic);
+    time.tv_sec as u64 * 1_000_000_000 + time.tv_nsec as u64
+}
+
+// ————————————————————————————————— Output ————————————————————————————————— //
+
+/// Destination of the statistics report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatsOutput {
+    /// Prints the report on the standard output.
+    Print,
+    /// Writes the report to the file at the given path.
+    File(CString),
+}
+
+impl StatsOutput {
+    /// Parses the value of the `FOLD_STATS` environment variable or `--stats` option: `1` (or an empty value) prints
+    /// the report, `0` disables it, and any other value is used as the path of the report file.
+    pub fn parse(value: &[u8]) -> Option<Self> {
+        match value {
+            b"" | b"1" => Some(StatsOutput::Print),
+            b"0" => None,
+            path => CString::new(path).ok().map(StatsOutput::File),
+        }
+    }
+}
+
+// —————————————————————————————— Statistics ———————————————————————————————— //
+
+/// Time spent by a phase on a single element of the [`Manifold`][crate::Manifold], recorded only if per-element
+/// statistics are enabled.
+#[derive(Debug)]
+pub struct ElementStats {
+    /// Description of the element.
+    pub element: String,
+    /// Wall time spent processing the element, in nanoseconds.
+    pub elapsed: u64,
+}
+
+/// Time spent executing a phase.
+#[derive(Debug)]
+pub struct PhaseStats {
+    /// Name of the phase.
+    pub name: String,
+    /// Wall time spent executing the phase, in nanoseconds, or `None` if the phase did not complete.
+    pub elapsed: Option<u64>,
+    /// Number of calls to the module of the phase, phase hooks included.
+    pub calls: usize,
+    /// Wall time spent in the module of the phase, in nanoseconds.
+    pub busy: u64,
+    /// Elements processed by the phase, in processing order. Empty unless per-element statistics are enabled.
+    pub elements: Vec<ElementStats>,
+}
+
+/// Statistics of the execution of a chain.
+///
+/// Timings are recorded by the driver, while counters are updated by the modules if the statistics are present in the
+/// [`Manifold::shared`][crate::Manifold::shared] map under [`FOLD_STATS_KEY`]. The report is emitted at the end of
+/// [`Fold::run`][crate::Fold::run], or right before control is transferred to the program.
+#[derive(Debug)]
+pub struct Stats {
+    output: StatsOutput,
+    per_element: bool,
+    phases: Vec<PhaseStats>,
+    relocations: BTreeMap<u32, usize>,
+    bytes_mapped: usize,
+}
+
+impl Stats {
+    /// Creates empty statistics, reported to `output`. Timings are aggregated per phase, unless `per_element` is set
+    /// in which case the time spent on each element is recorded as well.
+    pub fn new(output: StatsOutput, per_element: bool) -> Self {
+        Self {
+            output,
+            per_element,
+            phases: Vec::new(),
+            relocations: BTreeMap::new(),
+            bytes_mapped: 0,
+        }
+    }
+
+    /// Returns `true` if the time spent on each element is recorded.
+    pub fn per_element(&self) -> bool {
+        self.per_element
+    }
+
+    /// Returns the timings of the executed phases.
+    pub fn phases(&self) -> &[PhaseStats] {
+        &self.phases
+    }
+
+    /// Returns the number of relocations applied, indexed by relocation type.
+    pub fn relocations(&self) -> &BTreeMap<u32, usize> {
+        &self.relocations
+    }
+
+    /// Returns the number of bytes mapped while loading objects.
+    pub fn bytes_mapped(&self) -> usize {
+        self.bytes_mapped
+    }
+
+    /// Adds relocations counts, indexed by relocation type.
+    pub fn count_relocations(&mut self, counts: &BTreeMap<u32, usize>) {
+        for (typ, count) in counts {
+            *self.relocations.entry(*typ).or_default() += count;
+        }
+    }
+
+    /// Adds `bytes` to the number of mapped bytes.
+    pub fn count_mapped(&mut self, bytes: usize) {
+        self.bytes_mapped += bytes;
+    }
+
+    pub(crate) fn begin_phase(&mut self, name: &str) {
+        self.phases.push(PhaseStats {
+            name: name.into(),
+            elapsed: None,
+            calls: 0,
+            busy: 0,
+            elements: Vec::new(),
+        });
+    }
+
+    pub(crate) fn end_phase(&mut self, elapsed: u64) {
+        if let Some(phase) = self.phases.last_mut() {
+            phase.elapsed = Some(elapsed);
+        }
+    }
+
+    /// Adds a call to the module of the current phase. `element` is only given if per-element statistics are enabled.
+    pub(crate) fn record_call(&mut self, element: Option<String>, elapsed: u64) {
+        if let Some(phase) = self.phases.last_mut() {
+            phase.calls += 1;
+            phase.busy += elapsed;
+            if let Some(element) = element {
+                phase.elements.push(ElementStats { element, elapsed });
+            }
+        }
+    }
+
+    /// Emits the report to its [`StatsOutput`].
+    pub fn report(&self) {
+        match &self.output {
+            StatsOutput::Print => println!("{self}"),
+            StatsOutput::File(path) => {
+                let mut report = String::new();
+                let _ = writeln!(report, "{self}");
+
+                let written = fs::open(
+                    path.as_c_str(),
+                    OFlags::WRONLY | OFlags::CREATE | OFlags::TRUNC | OFlags::CLOEXEC,
+                    Mode::from_raw_mode(0o644),
+                )
+                .and_then(|fd| io::write(&fd, report.as_bytes()));
+
+                if let Err(err) = written {
+                    log::warn!("Unable to write statistics to {path:?}: {err:?}");
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Fold statistics")?;
+
+        writeln!(f, "\nPhases:")?;
+        for phase in &self.phases {
+            match phase.elapsed {
+                Some(elapsed) => write!(f, "  {:>12}  {}", Duration(elapsed), phase.name)?,
+                None => write!(f, "  {:>12}  {} (not completed)", "-", phase.name)?,
+            }
+            writeln!(
+                f,
+                " ({} calls, {} in module)",
+                phase.calls,
+                Duration(phase.busy)
+            )?;
+            for element in &phase.elements {
+                writeln!(
+                    f,
+                    "  {:>12}    {}",
+                    Duration(element.elapsed),
+                    element.element
+                )?;
+            }
+        }
+        let total = self.phases.iter().filter_map(|p| p.elapsed).sum();
+        writeln!(f, "  {:>12}  total", Duration(total))?;
+
+        writeln!(f, "\nRelocations:")?;
+        for (typ, count) in &self.relocations {
+            writeln!(f, "  {count:>12}  {}", r_to_str(*typ, EM_X86_64))?;
+        }
+        let total = self.relocations.values().sum::<usize>();
+        writeln!(f, "  {total:>12}  total")?;
+
+        write!(f, "\nBytes mapped: {}", self.bytes_mapped)
+    }
+}
+
+/// A duration in nanoseconds, displayed in milliseconds.
+struct Duration(u64);
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = format!("{}.{:03} ms", self.0 / 1_000_000, (self.0 / 1_000) % 1_000);
+        f.pad(&ms)
+    }
+}
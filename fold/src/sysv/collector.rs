@@ -8,38 +8,233 @@ use alloc::vec::Vec;
 use core::ffi::CStr;
 use core::fmt::Debug;
 
-use goblin::elf::dynamic::DT_NEEDED;
+use goblin::elf::dynamic::{DT_NEEDED, DT_RPATH, DT_RUNPATH};
+use goblin::elf::section_header::SHT_DYNAMIC;
 use goblin::elf64::dynamic::Dyn;
 use log::trace;
 use rustix::fs;
 
 use crate::arena::Handle;
+use crate::contract::{Contract, KeyScope};
+use crate::driver::INITIAL_ELF_KEY;
 use crate::elf::{ElfItemIterator, Section};
+use crate::error::FoldError;
 use crate::file;
 use crate::manifold::Manifold;
 use crate::module::Module;
 use crate::object::{Object, SectionT};
+use crate::ordering::{Constraints, TAG_COLLECTION};
 use crate::share_map::ShareMapKey;
 use crate::sysv::error::SysvError;
+use crate::sysv::ldcache::LdCache;
+
+/// Returns the strings of the entries with tag `tag` of a dynamic section.
+fn read_dyn_strings(
+    sec: &Section,
+    tag: u64,
+    manifold: &Manifold,
+) -> Result<Vec<CString>, SysvError> {
+    let linked_dynstr = sec.get_linked_section(manifold)?.as_string_table()?;
+
+    let strings = ElfItemIterator::<Dyn>::from_section(sec)
+        .filter(|e| e.d_tag == tag)
+        .map(|e| e.d_val)
+        .map(|idx| linked_dynstr.get_symbol(idx as usize).map(CStr::to_owned))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(strings)
+}
 
 /// Returns the name of all dependencies of a given object
 fn read_deps(sec: &Section, manifold: &Manifold) -> Result<Vec<CString>, SysvError> {
-    let mut deps = Vec::new();
+    read_dyn_strings(sec, DT_NEEDED, manifold)
+}
 
-    let linked_dynstr = sec.get_linked_section(manifold)?.as_string_table()?;
+/// Returns the libraries of the [`SYSV_COLLECTOR_PRELOAD_KEY`] if `obj` is the main object, or nothing otherwise.
+fn preloads(obj: Handle<Object>, manifold: &Manifold) -> Vec<CString> {
+    if manifold.shared.get(INITIAL_ELF_KEY) != Ok(&obj) {
+        return Vec::new();
+    }
+
+    manifold
+        .shared
+        .get(SYSV_COLLECTOR_PRELOAD_KEY)
+        .map(|libs| {
+            libs.iter()
+                .filter_map(|lib| CString::new(lib.as_str()).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Records the libraries named `names` as dependencies of `obj`, in order. Libraries which are not loaded yet are
+/// loaded and added to `deps`, the others are shared with the objects which loaded them first.
+fn add_dependencies(
+    obj: Handle<Object>,
+    names: Vec<CString>,
+    deps: &mut Vec<SysvCollectorEntry>,
+    manifold: &mut Manifold,
+) -> Result<(), Box<dyn Debug>> {
+    // Libraries removed from the manifold since they were collected are loaded again if still needed.
+    deps.retain(|entry| manifold.objects.contains(entry.obj));
+
+    for filename in names {
+        let dep = match deps.iter().find(|d| d.name == filename) {
+            Some(entry) => entry.obj,
+            None => {
+                let path_lib = find_library(&filename, obj, manifold)?;
+
+                let file_fd = file::open_file_ro(path_lib.as_str()).expect("Target is not a file");
+
+                let file = file::map_file(file_fd);
+                let path = CString::new(path_lib).map_err(|_| FoldError::InvalidString)?;
+                let dep = manifold.add_elf_file(file, path);
+
+                deps.push(SysvCollectorEntry {
+                    name: filename,
+                    obj: dep,
+                });
+                dep
+            }
+        };
+
+        // A preloaded library may also be a dependency of the object.
+        if dep != obj && !manifold[obj].dependencies.contains(&dep) {
+            manifold[obj].dependencies.push(dep);
+        }
+    }
+
+    Ok(())
+}
+
+// —————————————————————————————— Search paths —————————————————————————————— //
+
+/// Value of `$LIB` in run-time search paths.
+const LIB: &str = "lib64";
+/// Value of `$PLATFORM` in run-time search paths.
+const PLATFORM: &str = "x86_64";
+
+/// Run-time search paths of an object, from its `DT_RPATH` and `DT_RUNPATH` entries.
+#[derive(Default)]
+struct RunPaths {
+    rpath: Vec<String>,
+    runpath: Vec<String>,
+}
+
+impl RunPaths {
+    /// Reads the run-time search paths of `obj`, expanding `$ORIGIN`, `$LIB` and `$PLATFORM`.
+    ///
+    /// In secure mode, paths relative to `$ORIGIN` are ignored.
+    fn of(obj: Handle<Object>, manifold: &Manifold) -> Result<Self, SysvError> {
+        let object = &manifold[obj];
+        let Some(dynamic) = object
+            .sections
+            .iter()
+            .map(|s| &manifold[*s])
+            .find(|s| s.tag == SHT_DYNAMIC)
+        else {
+            return Ok(Self::default());
+        };
+
+        let path = object.display_path();
+        let origin = match path.rfind('/') {
+            Some(0) => "/",
+            Some(idx) => &path[..idx],
+            None => ".",
+        };
+        let secure = manifold.env.is_secure();
+
+        let read = |tag| -> Result<Vec<String>, SysvError> {
+            let paths = read_dyn_strings(dynamic, tag, manifold)?
+                .iter()
+                .flat_map(|list| list.to_bytes().split(|b| *b == b':'))
+                .filter(|p| !p.is_empty())
+                .map(String::from_utf8_lossy)
+                .filter(|p| !(secure && p.contains("ORIGIN")))
+                .map(|p| expand(&p, origin))
+                .collect();
+            Ok(paths)
+        };
+
+        Ok(Self {
+            rpath: read(DT_RPATH)?,
+            runpath: read(DT_RUNPATH)?,
+        })
+    }
+}
+
+/// Expands the `$ORIGIN`, `$LIB` and `$PLATFORM` tokens (or their `${...}` forms) of a run-time search path.
+fn expand(path: &str, origin: &str) -> String {
+    [("ORIGIN", origin), ("LIB", LIB), ("PLATFORM", PLATFORM)]
+        .into_iter()
+        .fold(path.to_owned(), |path, (token, value)| {
+            path.replace(&format!("${{{token}}}"), value)
+                .replace(&format!("${token}"), value)
+        })
+}
+
+/// Searches the library `filename` needed by `requester`, and returns its path.
+///
+/// Names containing a `/` are paths, and are not searched. Otherwise, the directories are searched in the following
+/// order:
+/// - The `DT_RPATH` of `requester`, then the one of the main object, unless they have a `DT_RUNPATH`.
+/// - The [`SYSV_COLLECTOR_LIBRARY_PATH_KEY`].
+/// - The `DT_RUNPATH` of `requester`.
+/// - The [`SYSV_COLLECTOR_SEARCH_PATHS_KEY`], such that the libraries next to the target and musl's `libc.so` are not
+///   shadowed by the system's ones.
+/// - The libraries, then the directories of the [`SYSV_COLLECTOR_CACHE_KEY`].
+fn find_library(
+    filename: &CStr,
+    requester: Handle<Object>,
+    manifold: &Manifold,
+) -> Result<String, Box<dyn Debug>> {
+    if filename.to_bytes().contains(&b'/') {
+        return match filename.to_str() {
+            Ok(path) if fs::stat(path).is_ok() => Ok(path.to_owned()),
+            _ => Err(SysvError::DependencyNotFound(filename.to_owned()).into()),
+        };
+    }
 
-    // Add all entries marked with DT_NEEDED to the object's dependencies
-    deps.append(
-        &mut ElfItemIterator::<Dyn>::from_section(sec)
-            .filter(|e| e.d_tag == DT_NEEDED)
-            .map(|e| e.d_val)
-            .map(|idx| linked_dynstr.get_symbol(idx as usize).map(CStr::to_owned))
-            .collect::<Result<Vec<_>, _>>()?,
-    );
+    let library_path = manifold
+        .shared
+        .get(SYSV_COLLECTOR_LIBRARY_PATH_KEY)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let search_paths = manifold.shared.get(SYSV_COLLECTOR_SEARCH_PATHS_KEY)?;
+
+    let RunPaths { mut rpath, runpath } = RunPaths::of(requester, manifold)?;
+    if !runpath.is_empty() {
+        rpath.clear();
+    } else if let Ok(&main) = manifold.shared.get(INITIAL_ELF_KEY) {
+        let main_paths = RunPaths::of(main, manifold)?;
+        if main != requester && main_paths.runpath.is_empty() {
+            rpath.extend(main_paths.rpath);
+        }
+    }
 
-    Ok(deps)
+    let name = filename.to_str().unwrap();
+    let exists = |p: &String| fs::stat(p.as_str()).is_ok();
+    let search = |dirs: &mut dyn Iterator<Item = &String>| {
+        dirs.map(|p| format!("{}/{}", p, name)).find(exists)
+    };
+
+    let cache = manifold.shared.get(SYSV_COLLECTOR_CACHE_KEY).ok();
+    let path = search(&mut rpath.iter().chain(library_path).chain(&runpath))
+        .or_else(|| search(&mut search_paths.iter()))
+        .or_else(|| {
+            let cache = cache?;
+            cache
+                .find(name)
+                .map(str::to_owned)
+                .filter(exists)
+                .or_else(|| search(&mut cache.directories().iter()))
+        })
+        .ok_or_else(|| SysvError::DependencyNotFound(filename.to_owned()))?;
+    Ok(path)
 }
 
+// ——————————————————————————————— Collectors ——————————————————————————————— //
+
 #[derive(Clone)]
 pub struct SysvCollectorEntry {
     /// Filename of the dependency
@@ -54,12 +249,25 @@ impl Debug for SysvCollectorEntry {
     }
 }
 
+/// Directories searched for libraries by the collectors, after the [`SYSV_COLLECTOR_LIBRARY_PATH_KEY`].
 pub const SYSV_COLLECTOR_SEARCH_PATHS_KEY: ShareMapKey<Vec<String>> =
-    ShareMapKey::new("sysv_collector_search_paths");
+    ShareMapKey::new("sysv-collector", "search-paths").with_debug();
+/// Directories searched for libraries first, equivalent to `LD_LIBRARY_PATH`. This key is optional.
+pub const SYSV_COLLECTOR_LIBRARY_PATH_KEY: ShareMapKey<Vec<String>> =
+    ShareMapKey::new("sysv-collector", "library-path").with_debug();
+/// Libraries loaded before the dependencies of the main object, equivalent to `LD_PRELOAD`. Their symbols take
+/// precedence over the ones of the other dependencies. This key is optional.
+pub const SYSV_COLLECTOR_PRELOAD_KEY: ShareMapKey<Vec<String>> =
+    ShareMapKey::new("sysv-collector", "preload").with_debug();
+/// System library cache, searched after the [`SYSV_COLLECTOR_SEARCH_PATHS_KEY`]. This key is optional.
+pub const SYSV_COLLECTOR_CACHE_KEY: ShareMapKey<LdCache> =
+    ShareMapKey::new("sysv-collector", "cache");
 pub const SYSV_COLLECTOR_REMAP_KEY: ShareMapKey<BTreeMap<String, Option<CString>>> =
-    ShareMapKey::new("sysv_collector_map");
+    ShareMapKey::new("sysv-collector", "remap").with_debug();
+/// Libraries loaded by the collectors, with the name they were needed as. Entries of objects removed from the manifold
+/// are pruned by the next collection, and must be skipped with `objects.get` until then.
 pub const SYSV_COLLECTOR_RESULT_KEY: ShareMapKey<Vec<SysvCollectorEntry>> =
-    ShareMapKey::new("sysv_collector_result");
+    ShareMapKey::new("sysv-collector", "result").with_debug();
 
 pub struct SysvCollector;
 
@@ -68,6 +276,16 @@ impl Module for SysvCollector {
         "sysv-collector"
     }
 
+    fn constraints(&self) -> Constraints {
+        Constraints::new().tag(TAG_COLLECTION)
+    }
+
+    fn contract(&self) -> Contract {
+        Contract::new()
+            .requires(KeyScope::Manifold, SYSV_COLLECTOR_SEARCH_PATHS_KEY)
+            .provides(KeyScope::Manifold, SYSV_COLLECTOR_RESULT_KEY)
+    }
+
     fn process_section(
         &mut self,
         hsec: Handle<Section>,
@@ -85,35 +303,13 @@ impl Module for SysvCollector {
             .cloned()
             .unwrap_or_default();
 
-        // Compute the dependencies of the current object, and removes the ones already found
-        let new_deps = read_deps(section, manifold)?
+        // Compute the dependencies of the current object, preceded by the preloaded libraries for the main object
+        let names = preloads(hobj, manifold)
             .into_iter()
-            .filter(|n| deps.iter().all(|d| d.name != *n))
+            .chain(read_deps(section, manifold)?)
             .collect::<Vec<_>>();
 
-        // Loads all the newly found dependenciesadd_elf
-        for filename in new_deps {
-            let path_lib = manifold
-                .shared
-                .get(SYSV_COLLECTOR_SEARCH_PATHS_KEY)
-                .expect("Search paths not set")
-                .iter()
-                .map(|p| format!("{}/{}", p, filename.to_str().unwrap()))
-                .find(|p| fs::stat(p.as_str()).is_ok())
-                .ok_or_else(|| SysvError::DependencyNotFound(filename.clone()))?;
-
-            let file_fd = file::open_file_ro(path_lib.as_str()).expect("Target is not a file");
-
-            let file = file::map_file(file_fd);
-            let obj = manifold.add_elf_file(file, filename.clone());
-
-            manifold[hobj].dependencies.push(obj);
-
-            deps.push(SysvCollectorEntry {
-                name: filename,
-                obj,
-            });
-        }
+        add_dependencies(hobj, names, &mut deps, manifold)?;
 
         manifold.shared.insert(SYSV_COLLECTOR_RESULT_KEY, deps);
 
@@ -135,6 +331,16 @@ impl Module for SysvRemappingCollector {
         "sysv-remapping-collector"
     }
 
+    fn constraints(&self) -> Constraints {
+        Constraints::new().tag(TAG_COLLECTION)
+    }
+
+    fn contract(&self) -> Contract {
+        Contract::new()
+            .requires(KeyScope::Manifold, SYSV_COLLECTOR_SEARCH_PATHS_KEY)
+            .provides(KeyScope::Manifold, SYSV_COLLECTOR_RESULT_KEY)
+    }
+
     fn process_section(
         &mut self,
         hsec: Handle<Section>,
@@ -176,41 +382,15 @@ impl Module for SysvRemappingCollector {
             }
         });
 
-        // Filter out already found dependencies
-        let new_deps = new_deps
+        // Preloaded libraries come first for the main object
+        let names = preloads(hobj, manifold)
             .into_iter()
-            .filter(|n| deps.iter().all(|d| d.name != *n))
+            .chain(new_deps)
             .collect::<Vec<_>>();
 
-        trace!(
-            "[{}] New deps: {:?}",
-            manifold[hobj].display_path(),
-            new_deps
-        );
+        trace!("[{}] Deps: {:?}", manifold[hobj].display_path(), names);
 
-        // Loads all the newly found dependencies
-        for filename in new_deps {
-            let path_lib = manifold
-                .shared
-                .get(SYSV_COLLECTOR_SEARCH_PATHS_KEY)
-                .expect("Search paths not set")
-                .iter()
-                .map(|p| format!("{}/{}", p, filename.to_str().unwrap()))
-                .find(|p| fs::stat(p.as_str()).is_ok())
-                .ok_or_else(|| SysvError::DependencyNotFound(filename.clone()))?;
-
-            let file_fd = file::open_file_ro(path_lib.as_str()).expect("Target is not a file");
-
-            let file = file::map_file(file_fd);
-            let obj = manifold.add_elf_file(file, filename.clone());
-
-            manifold[hobj].dependencies.push(obj);
-
-            deps.push(SysvCollectorEntry {
-                name: filename,
-                obj,
-            });
-        }
+        add_dependencies(hobj, names, &mut deps, manifold)?;
 
         manifold.shared.insert(SYSV_COLLECTOR_RESULT_KEY, deps);
 
@@ -1,6 +1,8 @@
 use alloc::boxed::Box;
 use alloc::ffi::CString;
 
+use rustix::io::Errno;
+
 use crate::error::FoldError;
 
 #[derive(Debug)]
@@ -8,6 +10,17 @@ pub enum SysvError {
     FoldError(FoldError),
     RelaSectionWithoutVirtualAdresses,
     DependencyNotFound(CString),
+    /// The dependency `file` does not define the `version` required by `object`.
+    MissingVersion {
+        version: CString,
+        file: CString,
+        object: CString,
+    },
+    /// The memory of `object` could not be reserved or mapped.
+    Mapping {
+        object: CString,
+        errno: Errno,
+    },
     Other,
 }
 
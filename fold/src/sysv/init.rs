@@ -0,0 +1,205 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ffi::c_char;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+use goblin::elf::dynamic::{
+    DT_FINI, DT_FINI_ARRAY, DT_FINI_ARRAYSZ, DT_INIT, DT_INIT_ARRAY, DT_INIT_ARRAYSZ,
+    DT_PREINIT_ARRAY, DT_PREINIT_ARRAYSZ,
+};
+
+use crate::arena::Handle;
+use crate::contract::{Contract, KeyScope};
+use crate::manifold::Manifold;
+use crate::module::Module;
+use crate::object::Object;
+use crate::ordering::{Anchor, Constraints, TAG_INIT, TAG_RELOCATION, TAG_START};
+use crate::share_map::ShareMapKey;
+use crate::sysv::loader::SYSV_LOADER_BASE_ADDR;
+use crate::sysv::relocation::add_deps;
+
+/// Address of the function running the finalizers, passed to the program in `rdx` by
+/// [`SysvStart`][crate::sysv::start::SysvStart]. musl ignores it: [`MuslInit`][crate::musl::MuslInit] registers the
+/// function with musl's `atexit` instead.
+pub const SYSV_INIT_FINI_KEY: ShareMapKey<usize> = ShareMapKey::new("sysv-init", "fini");
+
+/// Initializers receive `argc`, `argv` and `envp`, as with glibc. Other C libraries ignore them.
+type InitFn = unsafe extern "C" fn(i32, *const *const c_char, *const *const c_char);
+type FiniFn = unsafe extern "C" fn();
+
+/// Finalizers of the objects, in execution order. Taken by [`run_finalizers`] such that they run only once.
+static FINALIZERS: AtomicPtr<Vec<Finalizers>> = AtomicPtr::new(ptr::null_mut());
+
+// ————————————————————————————— Initialization ————————————————————————————— //
+
+/// Runs the initializers of the processed object and of its dependencies, and registers their finalizers.
+///
+/// The `DT_PREINIT_ARRAY` of the processed object runs first. Then for each object in reverse dependency order
+/// (dependencies before the objects depending on them, the processed object last), `DT_INIT` runs, followed by the
+/// functions of `DT_INIT_ARRAY`. Finalizers run in the opposite order when the function stored in
+/// [`SYSV_INIT_FINI_KEY`] is called: `DT_FINI_ARRAY` in reverse order, then `DT_FINI`.
+pub struct SysvInit;
+
+impl Module for SysvInit {
+    fn name(&self) -> &'static str {
+        "sysv-init"
+    }
+
+    fn constraints(&self) -> Constraints {
+        Constraints::new()
+            .tag(TAG_INIT)
+            .runs_after(Anchor::tag(TAG_RELOCATION))
+            .runs_before(Anchor::tag(TAG_START))
+    }
+
+    fn contract(&self) -> Contract {
+        Contract::new()
+            .requires(KeyScope::Object, SYSV_LOADER_BASE_ADDR)
+            .provides(KeyScope::Manifold, SYSV_INIT_FINI_KEY)
+    }
+
+    fn process_object(
+        &mut self,
+        obj: Handle<Object>,
+        manifold: &mut Manifold,
+    ) -> Result<(), Box<dyn core::fmt::Debug>> {
+        let mut order = Vec::new();
+        add_deps(obj, manifold, &mut order);
+
+        // Arguments of the initializers, as null-terminated arrays.
+        let args = null_terminated(manifold.env.args.iter().map(|a| a.as_ptr()));
+        let envp = null_terminated(manifold.env.envp.iter().map(|e| e.as_ptr()));
+        let argc = manifold.env.args.len() as i32;
+
+        let preinit = Functions::of(
+            &manifold[obj],
+            manifold,
+            DT_PREINIT_ARRAY,
+            DT_PREINIT_ARRAYSZ,
+        );
+        for function in preinit.array {
+            log::info!("Running preinit function {function:#x}");
+            unsafe { run_init(*function, argc, &args, &envp) };
+        }
+
+        let mut finalizers = Vec::new();
+        for handle in order {
+            let object = &manifold[handle];
+            let init = Functions::of(object, manifold, DT_INIT_ARRAY, DT_INIT_ARRAYSZ);
+            log::info!("Running initializers of {}", object.display_path());
+            if let Some(function) = init.single(DT_INIT) {
+                unsafe { run_init(function, argc, &args, &envp) };
+            }
+            for function in init.array {
+                unsafe { run_init(*function, argc, &args, &envp) };
+            }
+
+            let fini = Functions::of(object, manifold, DT_FINI_ARRAY, DT_FINI_ARRAYSZ);
+            finalizers.push(Finalizers {
+                single: fini.single(DT_FINI),
+                array: fini.array,
+            });
+        }
+
+        // Dependencies are finalized after the objects depending on them.
+        finalizers.reverse();
+        FINALIZERS.store(Box::into_raw(Box::new(finalizers)), Ordering::Release);
+        manifold
+            .shared
+            .insert(SYSV_INIT_FINI_KEY, run_finalizers as *const () as usize);
+
+        Ok(())
+    }
+}
+
+/// Initialization or finalization functions of an object, read from its dynamic section.
+struct Functions {
+    /// Base address of the object.
+    base: usize,
+    /// Entries of the dynamic section of the object.
+    entries: Vec<(u64, u64)>,
+    /// Functions of the array, already relocated.
+    array: &'static [usize],
+}
+
+impl Functions {
+    /// Reads the function array given by the `array` and `size` tags of the dynamic section of `obj`.
+    fn of(obj: &Object, manifold: &Manifold, array: u64, size: u64) -> Self {
+        let base = obj
+            .shared
+            .get(SYSV_LOADER_BASE_ADDR)
+            .copied()
+            .unwrap_or_default();
+        let entries: Vec<_> = obj
+            .dynamic_entries(manifold)
+            .map(|e| (e.d_tag, e.d_val))
+            .collect();
+        let value = |tag| entries.iter().find(|(t, _)| *t == tag).map(|(_, v)| *v);
+
+        let array = match (value(array), value(size)) {
+            (Some(addr), Some(size)) => unsafe {
+                core::slice::from_raw_parts(
+                    (base + addr as usize) as *const usize,
+                    size as usize / size_of::<usize>(),
+                )
+            },
+            _ => &[],
+        };
+
+        Self {
+            base,
+            entries,
+            array,
+        }
+    }
+
+    /// Returns the address of the function given by the `tag` entry of the dynamic section (`DT_INIT` or `DT_FINI`).
+    fn single(&self, tag: u64) -> Option<usize> {
+        self.entries
+            .iter()
+            .find(|(t, _)| *t == tag)
+            .map(|(_, addr)| self.base + *addr as usize)
+    }
+}
+
+/// Calls an initializer. Entries set to `0` or `-1`, used as markers by some toolchains, are skipped.
+unsafe fn run_init(function: usize, argc: i32, args: &[*const c_char], envp: &[*const c_char]) {
+    if function == 0 || function == usize::MAX {
+        return;
+    }
+    let function: InitFn = core::mem::transmute(function);
+    function(argc, args.as_ptr(), envp.as_ptr());
+}
+
+fn null_terminated(items: impl Iterator<Item = *const c_char>) -> Vec<*const c_char> {
+    items.chain([ptr::null()]).collect()
+}
+
+// ——————————————————————————————— Finalization —————————————————————————————— //
+
+/// Finalizers of an object.
+struct Finalizers {
+    /// `DT_FINI`.
+    single: Option<usize>,
+    /// `DT_FINI_ARRAY`, run in reverse order.
+    array: &'static [usize],
+}
+
+/// Runs the finalizers of all initialized objects. The C libraries which honor `rdx` register it with `atexit`, as
+/// [`MuslInit`][crate::musl::MuslInit] does for musl.
+pub(crate) extern "C" fn run_finalizers() {
+    let finalizers = FINALIZERS.swap(ptr::null_mut(), Ordering::AcqRel);
+    if finalizers.is_null() {
+        return;
+    }
+
+    let finalizers = unsafe { Box::from_raw(finalizers) };
+    for object in finalizers.iter() {
+        let functions = object.array.iter().rev().copied().chain(object.single);
+        for function in functions.filter(|f| *f != 0 && *f != usize::MAX) {
+            let function: FiniFn = unsafe { core::mem::transmute(function) };
+            unsafe { function() };
+        }
+    }
+}
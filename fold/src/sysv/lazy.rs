@@ -0,0 +1,273 @@
+//! Lazy binding of PLT entries.
+//!
+//! When enabled with [`SYSV_RELOC_LAZY_BINDING_KEY`], [`SysvReloc`][super::relocation::SysvReloc] leaves the
+//! `R_X86_64_JUMP_SLOT` entries of the GOT pointing back at their PLT stub, and fills the reserved entries of the GOT:
+//! `GOT[1]` identifies the object, and `GOT[2]` holds the address of [`lazy_trampoline`]. On the first call to a
+//! function, the PLT stub pushes the index of its relocation and jumps to the trampoline, which resolves the symbol
+//! with the [`Manifold`], patches the GOT entry, and jumps to the function.
+
+use alloc::boxed::Box;
+use core::arch::naked_asm;
+use core::arch::x86_64::{__cpuid, __cpuid_count};
+use core::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+use goblin::elf::dynamic::{
+    DF_1_NOW, DF_BIND_NOW, DT_BIND_NOW, DT_FLAGS, DT_FLAGS_1, DT_JMPREL, DT_PLTGOT,
+};
+use goblin::elf::section_header::SHT_RELA;
+use goblin::elf64::reloc::{self, Rela};
+use plain::Plain;
+
+use crate::arena::Handle;
+use crate::exit::exit_error;
+use crate::manifold::Manifold;
+use crate::object::Object;
+use crate::scope::{Scope, SymbolLookup};
+use crate::share_map::ShareMapKey;
+use crate::sysv::loader::SYSV_LOADER_BASE_ADDR;
+
+/// Enables lazy binding for the objects which do not require immediate binding.
+pub const SYSV_RELOC_LAZY_BINDING_KEY: ShareMapKey<bool> =
+    ShareMapKey::new("sysv-reloc", "lazy-binding").with_debug();
+
+/// Manifold used to resolve symbols after the program started.
+///
+/// The manifold lives in the frame of [`Fold::run`][crate::Fold::run], which never returns once the program is started:
+/// the program's stack grows below it, and it is never moved nor dropped.
+static MANIFOLD: AtomicPtr<Manifold> = AtomicPtr::new(core::ptr::null_mut());
+/// Scope of the lookups, computed by the relocation phase.
+static SCOPE: AtomicPtr<Scope> = AtomicPtr::new(core::ptr::null_mut());
+
+/// The PLT of an object which is bound lazily.
+#[derive(Debug, Clone, Copy)]
+pub struct LazyPlt {
+    /// Virtual address of the GOT (`DT_PLTGOT`).
+    got: usize,
+    /// Virtual address of the PLT relocations (`DT_JMPREL`).
+    jmprel: usize,
+}
+
+impl LazyPlt {
+    /// Returns the PLT of `obj` if it must be bound lazily: lazy binding must be enabled in the manifold and supported
+    /// by the processor (`xsave`), and the object must have a PLT and not require immediate binding (`DT_BIND_NOW`,
+    /// `DF_BIND_NOW` or `DF_1_NOW`).
+    pub fn of(obj: &Object, manifold: &Manifold) -> Option<Self> {
+        if manifold.shared.get(SYSV_RELOC_LAZY_BINDING_KEY) != Ok(&true) {
+            return None;
+        }
+        let Some(size) = xsave_size() else {
+            log::warn!(
+                "Lazy binding requires xsave, binding {} now",
+                obj.display_path()
+            );
+            return None;
+        };
+        XSAVE_SIZE.store(size, Ordering::Relaxed);
+
+        let (mut got, mut jmprel) = (None, None);
+        for entry in obj.dynamic_entries(manifold) {
+            match entry.d_tag {
+                DT_PLTGOT => got = Some(entry.d_val as usize),
+                DT_JMPREL => jmprel = Some(entry.d_val as usize),
+                DT_BIND_NOW => return None,
+                DT_FLAGS if entry.d_val & DF_BIND_NOW != 0 => return None,
+                DT_FLAGS_1 if entry.d_val & DF_1_NOW != 0 => return None,
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            got: got?,
+            jmprel: jmprel?,
+        })
+    }
+
+    /// Returns `true` if the relocation section at virtual address `addr` holds the PLT relocations.
+    pub fn is_jmprel(&self, addr: usize) -> bool {
+        self.jmprel == addr
+    }
+
+    /// Fills the reserved entries of the GOT of `obj`, such that its PLT stubs call the resolver.
+    pub fn install(&self, obj: Handle<Object>, manifold: &Manifold) {
+        let base = manifold[obj]
+            .shared
+            .get(SYSV_LOADER_BASE_ADDR)
+            .copied()
+            .unwrap_or_default();
+        let got = (base + self.got) as *mut u64;
+
+        // The handle is never freed: it is needed as long as the program runs.
+        let link_map = Box::into_raw(Box::new(obj));
+        unsafe {
+            got.add(1).write(link_map as u64);
+            got.add(2).write(lazy_trampoline as *const () as u64);
+        }
+    }
+}
+
+/// Sets the manifold and scope used to resolve the symbols of the lazily bound objects.
+pub fn set_resolver(manifold: &Manifold, scope: Scope) {
+    // The scope is never freed: it is needed as long as the program runs.
+    SCOPE.store(Box::into_raw(Box::new(scope)), Ordering::Release);
+    MANIFOLD.store(
+        manifold as *const Manifold as *mut Manifold,
+        Ordering::Release,
+    );
+}
+
+// ———————————————————————————————— Resolver ———————————————————————————————— //
+
+/// Entry point of the lazy resolution, called by the first PLT stub (`PLT0`) of an object.
+///
+/// The stack holds the `GOT[1]` of the object, then the index of the relocation of the called function, then the
+/// return address of the call. The integer registers which may hold arguments, `r10` (the static chain) and the whole
+/// extended state (`xsave`), including the upper parts of the vector registers, are preserved.
+#[unsafe(naked)]
+unsafe extern "C" fn lazy_trampoline() {
+    naked_asm!(
+        // `rbx` keeps the frame, and gives access to the arguments pushed by the PLT stubs.
+        "push rbx",
+        "mov rbx, rsp",
+        "push rax",
+        "push rcx",
+        "push rdx",
+        "push rsi",
+        "push rdi",
+        "push r8",
+        "push r9",
+        "push r10",
+        // The save area must be aligned on 64 bytes, and its header cleared.
+        "and rsp, -64",
+        "sub rsp, [rip + {size}]",
+        "xor eax, eax",
+        "mov [rsp + 512], rax",
+        "mov [rsp + 520], rax",
+        "mov [rsp + 528], rax",
+        "mov [rsp + 536], rax",
+        "mov [rsp + 544], rax",
+        "mov [rsp + 552], rax",
+        "mov [rsp + 560], rax",
+        "mov [rsp + 568], rax",
+        "mov eax, -1",
+        "mov edx, -1",
+        "xsave64 [rsp]",
+        "mov rdi, [rbx + 8]",
+        "mov rsi, [rbx + 16]",
+        "call {resolve}",
+        "mov r11, rax",
+        "mov eax, -1",
+        "mov edx, -1",
+        "xrstor64 [rsp]",
+        "lea rsp, [rbx - 64]",
+        "pop r10",
+        "pop r9",
+        "pop r8",
+        "pop rdi",
+        "pop rsi",
+        "pop rdx",
+        "pop rcx",
+        "pop rax",
+        "pop rbx",
+        // Drop the arguments pushed by the PLT stubs, and call the function with the original return address.
+        "add rsp, 16",
+        "jmp r11",
+        size = sym XSAVE_SIZE,
+        resolve = sym lazy_resolve,
+    );
+}
+
+/// Size of the area saved by `xsave` for the enabled state components, rounded up to 64 bytes.
+static XSAVE_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the size of the `xsave` area of the enabled state components, or `None` if the processor or the kernel
+/// do not support `xsave`.
+fn xsave_size() -> Option<usize> {
+    // CPUID.01H:ECX.OSXSAVE[bit 27]
+    let features = __cpuid(1);
+    if features.ecx & (1 << 27) == 0 {
+        return None;
+    }
+    // CPUID.0DH:EBX, size required by the components enabled in XCR0.
+    let size = __cpuid_count(0xd, 0).ebx as usize;
+    Some(size.next_multiple_of(64))
+}
+
+/// Serializes the resolutions: program threads may call unbound functions concurrently.
+static RESOLVER_LOCK: AtomicBool = AtomicBool::new(false);
+
+/// Resolves the symbol of the PLT relocation at `index` in the object identified by `link_map`, patches the GOT, and
+/// returns the address of the function.
+extern "C" fn lazy_resolve(link_map: *const Handle<Object>, index: usize) -> u64 {
+    while RESOLVER_LOCK
+        .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        core::hint::spin_loop();
+    }
+    let target = resolve(unsafe { *link_map }, index);
+    RESOLVER_LOCK.store(false, Ordering::Release);
+    target
+}
+
+fn resolve(obj: Handle<Object>, index: usize) -> u64 {
+    let manifold = unsafe { &*MANIFOLD.load(Ordering::Acquire) };
+    let scope = unsafe { &*SCOPE.load(Ordering::Acquire) };
+
+    let Some((rela, base)) = plt_relocation(obj, index, manifold) else {
+        log::error!(
+            "Invalid PLT relocation {index} in {}",
+            manifold[obj].display_path()
+        );
+        exit_error();
+    };
+
+    let sym = reloc::r_sym(rela.r_info) as usize;
+    let lookup = SymbolLookup::reference(obj, sym, manifold);
+    let Ok((container, sym)) = lookup
+        .as_ref()
+        .map_err(Clone::clone)
+        .and_then(|lookup| manifold.resolve_symbol_in(scope, lookup))
+    else {
+        log::error!(
+            "Symbol lookup error: {}: undefined symbol {:?}",
+            manifold[obj].display_path(),
+            lookup.map(|lookup| lookup.name)
+        );
+        exit_error();
+    };
+
+    let target = manifold[container.obj]
+        .shared
+        .get(SYSV_LOADER_BASE_ADDR)
+        .copied()
+        .unwrap_or_default() as u64
+        + sym.st_value;
+    unsafe { ((base + rela.r_offset as usize) as *mut u64).write(target) };
+
+    log::trace!(
+        "Lazily bound PLT relocation {index} of {}",
+        manifold[obj].display_path()
+    );
+    target
+}
+
+/// Returns the PLT relocation at `index` of `obj`, and the base address of the object.
+fn plt_relocation(obj: Handle<Object>, index: usize, manifold: &Manifold) -> Option<(Rela, usize)> {
+    let object = &manifold[obj];
+    let plt = LazyPlt::of(object, manifold)?;
+    let section = object
+        .sections
+        .iter()
+        .map(|s| &manifold[*s])
+        .find(|s| s.tag == SHT_RELA && plt.is_jmprel(s.addr))?;
+
+    let size = core::mem::size_of::<Rela>();
+    let bytes = section
+        .mapping
+        .bytes()
+        .get(index * size..(index + 1) * size)?;
+    let rela = *Rela::from_bytes(bytes).ok()?;
+    let base = *object.shared.get(SYSV_LOADER_BASE_ADDR).ok()?;
+
+    Some((rela, base))
+}
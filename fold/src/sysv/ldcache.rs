@@ -0,0 +1,234 @@
+//! Lookup of libraries in the system's `ld.so.cache`, and in the directories listed by `/etc/ld.so.conf`.
+
+use alloc::borrow::ToOwned;
+use alloc::collections::btree_map::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::ffi::CStr;
+use core::mem::MaybeUninit;
+
+use rustix::fs::{self, Mode, OFlags, RawDir};
+use rustix::io;
+
+use crate::glob;
+
+/// Default location of the library cache.
+pub const LD_SO_CACHE: &str = "/etc/ld.so.cache";
+/// Default location of the library configuration.
+pub const LD_SO_CONF: &str = "/etc/ld.so.conf";
+
+/// Magic of the format used by glibc before 2.32, which may be followed by the new format.
+const MAGIC_OLD: &[u8] = b"ld.so-1.7.0";
+/// Magic and version of the format used by glibc since 2.7.
+const MAGIC_NEW: &[u8] = b"glibc-ld.so.cache1.1";
+
+/// Size of the header of the old format.
+const HEADER_OLD: usize = 16;
+/// Size of an entry of the old format.
+const ENTRY_OLD: usize = 12;
+/// Size of the header of the new format.
+const HEADER_NEW: usize = 48;
+/// Size of an entry of the new format.
+const ENTRY_NEW: usize = 24;
+
+/// Flags of the entries describing x86_64 libraries.
+const FLAGS_X86_64: i32 = FLAG_ELF_LIBC6 | FLAG_X8664_LIB64;
+const FLAG_ELF_LIBC6: i32 = 0x0003;
+const FLAG_X8664_LIB64: i32 = 0x0300;
+
+/// Maximum nesting of `include` directives, to stop on include cycles.
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// Libraries listed in the system's `ld.so.cache`, and directories listed in `ld.so.conf`.
+///
+/// Both files are optional: a missing or malformed file is treated as empty.
+#[derive(Debug, Default, Clone)]
+pub struct LdCache {
+    /// Paths of the x86_64 libraries of the cache, indexed by file name.
+    libraries: BTreeMap<String, String>,
+    /// Directories listed in the configuration and its included files.
+    directories: Vec<String>,
+}
+
+impl LdCache {
+    /// Loads the system's [`LD_SO_CACHE`] and [`LD_SO_CONF`].
+    pub fn load() -> Self {
+        Self::from_files(LD_SO_CACHE, LD_SO_CONF)
+    }
+
+    /// Loads the cache and configuration files at the given paths.
+    pub fn from_files(cache: &str, conf: &str) -> Self {
+        let libraries = read_file(cache)
+            .map(|bytes| parse_cache(&bytes))
+            .unwrap_or_default();
+        let mut directories = Vec::new();
+        parse_conf(conf, &mut directories, 0);
+
+        Self {
+            libraries,
+            directories,
+        }
+    }
+
+    /// Returns the path of the library named `name` in the cache, if any.
+    pub fn find(&self, name: &str) -> Option<&str> {
+        self.libraries.get(name).map(String::as_str)
+    }
+
+    /// Returns the directories listed in the configuration, in order.
+    pub fn directories(&self) -> &[String] {
+        &self.directories
+    }
+}
+
+// —————————————————————————————————— Cache ————————————————————————————————— //
+
+/// Parses an `ld.so.cache` file, in the new format optionally preceded by the old one. Only x86_64 libraries are kept.
+fn parse_cache(bytes: &[u8]) -> BTreeMap<String, String> {
+    let mut libraries = BTreeMap::new();
+
+    // The new format follows the entries of the old one, aligned on 8 bytes.
+    let start = if bytes.starts_with(MAGIC_OLD) {
+        let Some(nlibs) = read_u32(bytes, 12) else {
+            return libraries;
+        };
+        (HEADER_OLD + nlibs as usize * ENTRY_OLD).next_multiple_of(8)
+    } else {
+        0
+    };
+
+    let Some(cache) = bytes.get(start..).filter(|c| c.starts_with(MAGIC_NEW)) else {
+        log::warn!("Unsupported ld.so.cache format");
+        return libraries;
+    };
+    let Some(nlibs) = read_u32(cache, 20) else {
+        return libraries;
+    };
+
+    for idx in 0..nlibs as usize {
+        let entry = HEADER_NEW + idx * ENTRY_NEW;
+        let (Some(flags), Some(key), Some(value)) = (
+            read_u32(cache, entry),
+            read_u32(cache, entry + 4),
+            read_u32(cache, entry + 8),
+        ) else {
+            break;
+        };
+
+        if flags as i32 != FLAGS_X86_64 {
+            continue;
+        }
+
+        // Strings offsets are relative to the start of the new format.
+        if let (Some(name), Some(path)) = (read_str(cache, key), read_str(cache, value)) {
+            libraries.entry(name).or_insert(path);
+        }
+    }
+
+    libraries
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 4)?;
+    Some(u32::from_ne_bytes(bytes.try_into().ok()?))
+}
+
+fn read_str(bytes: &[u8], offset: u32) -> Option<String> {
+    let string = CStr::from_bytes_until_nul(bytes.get(offset as usize..)?).ok()?;
+    string.to_str().ok().map(str::to_owned)
+}
+
+// —————————————————————————————— Configuration ————————————————————————————— //
+
+/// Appends the directories listed in the configuration file at `path` to `directories`, following `include`
+/// directives.
+fn parse_conf(path: &str, directories: &mut Vec<String>, depth: usize) {
+    if depth > MAX_INCLUDE_DEPTH {
+        log::warn!("Too many nested includes in {path}");
+        return;
+    }
+    let Some(content) = read_file(path) else {
+        return;
+    };
+    let content = String::from_utf8_lossy(&content);
+    let base = path.rsplit_once('/').map_or(".", |(dir, _)| dir);
+
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() || line.starts_with("hwcap ") {
+            continue;
+        }
+
+        if let Some(patterns) = line.strip_prefix("include ") {
+            for pattern in patterns.split_whitespace() {
+                let pattern = if pattern.starts_with('/') {
+                    pattern.to_owned()
+                } else {
+                    format!("{base}/{pattern}")
+                };
+                for file in expand(&pattern) {
+                    parse_conf(&file, directories, depth + 1);
+                }
+            }
+            continue;
+        }
+
+        // Directories are separated by spaces, tabs, commas or colons, and may be followed by `=TYPE`.
+        let dirs = line
+            .split([' ', '\t', ',', ':'])
+            .map(|dir| dir.split('=').next().unwrap_or_default())
+            .filter(|dir| !dir.is_empty());
+        for dir in dirs {
+            let dir = dir.trim_end_matches('/');
+            if !directories.iter().any(|d| d == dir) {
+                directories.push(dir.to_owned());
+            }
+        }
+    }
+}
+
+/// Expands a pattern whose last component may contain glob characters into the sorted list of matching files.
+fn expand(pattern: &str) -> Vec<String> {
+    let (dir, file) = pattern.rsplit_once('/').unwrap_or((".", pattern));
+    if !file.contains(['*', '?', '[']) {
+        return vec![pattern.to_owned()];
+    }
+
+    let Ok(fd) = fs::open(
+        dir,
+        OFlags::RDONLY | OFlags::DIRECTORY | OFlags::CLOEXEC,
+        Mode::empty(),
+    ) else {
+        return Vec::new();
+    };
+
+    let mut files = Vec::new();
+    let mut buf = [MaybeUninit::uninit(); 4096];
+    let mut entries = RawDir::new(fd, &mut buf);
+    while let Some(Ok(entry)) = entries.next() {
+        let name = entry.file_name().to_bytes();
+        if !name.starts_with(b".") && glob::matches(file.as_bytes(), name) {
+            files.push(format!("{dir}/{}", String::from_utf8_lossy(name)));
+        }
+    }
+
+    files.sort();
+    files
+}
+
+/// Reads the whole content of the file at `path`.
+fn read_file(path: &str) -> Option<Vec<u8>> {
+    let fd = fs::open(path, OFlags::RDONLY | OFlags::CLOEXEC, Mode::empty()).ok()?;
+
+    let mut content = Vec::new();
+    let mut buf = [0; 4096];
+    loop {
+        match io::read(&fd, &mut buf) {
+            Ok(0) => return Some(content),
+            Ok(len) => content.extend_from_slice(&buf[..len]),
+            Err(io::Errno::INTR) => {}
+            Err(_) => return None,
+        }
+    }
+}
@@ -1,18 +1,30 @@
 use alloc::boxed::Box;
 use core::ffi::c_void;
+use core::ops::Range;
+use core::ptr::null_mut;
 
+use goblin::elf::header::ET_DYN;
 use goblin::elf::program_header::PT_LOAD;
-use rustix::mm::{self, MapFlags, ProtFlags};
+use rustix::io::Errno;
+use rustix::mm::{self, MapFlags, MprotectFlags, ProtFlags};
 
 use crate::arena::Handle;
+use crate::contract::{Contract, KeyScope};
+use crate::env::AuxvType;
 use crate::file::MappingMut;
 use crate::manifold::Manifold;
 use crate::module::Module;
-use crate::object::Segment;
+use crate::object::{Object, Segment};
+use crate::ordering::{Constraints, TAG_LOADING};
 use crate::share_map::ShareMapKey;
+use crate::stats::FOLD_STATS_KEY;
+use crate::sysv::error::SysvError;
 
-pub const SYSV_LOADER_BASE_ADDR: ShareMapKey<usize> = ShareMapKey::new("sys_loader_base");
-pub const SYSV_LOADER_MAPPING: ShareMapKey<MappingMut> = ShareMapKey::new("sys_loader_mapping");
+const PAGE_SIZE: usize = 1 << 12;
+
+pub const SYSV_LOADER_BASE_ADDR: ShareMapKey<usize> =
+    ShareMapKey::new("sysv-loader", "base").with_debug();
+pub const SYSV_LOADER_MAPPING: ShareMapKey<MappingMut> = ShareMapKey::new("sysv-loader", "mapping");
 
 pub struct SysvLoader;
 
@@ -33,62 +45,62 @@ impl Module for SysvLoader {
         "sysv-loader"
     }
 
+    fn constraints(&self) -> Constraints {
+        Constraints::new().tag(TAG_LOADING)
+    }
+
+    fn contract(&self) -> Contract {
+        Contract::new()
+            .provides(KeyScope::Object, SYSV_LOADER_BASE_ADDR)
+            .provides(KeyScope::Segment, SYSV_LOADER_MAPPING)
+    }
+
     fn process_segment(
         &mut self,
         segment: Handle<Segment>,
         fold: &mut Manifold,
     ) -> Result<(), Box<dyn core::fmt::Debug>> {
         let s = &fold.segments[segment];
-        let obj = &mut fold.objects[s.obj];
+        let obj = &fold.objects[s.obj];
         log::info!("Loading segment of {}...", obj.display_path());
 
         if s.mem_size == 0 {
             return Ok(());
         }
 
-        let new_mapping = unsafe {
-            let offset = obj.shared.get(SYSV_LOADER_BASE_ADDR).copied().unwrap_or(0);
-
-            // Allocate memory
-            let addr = s.vaddr + offset;
-
-            let mapping = mm::mmap_anonymous(
-                (addr & (!0xfff)) as *mut c_void,
-                s.mem_size
-                    + (addr & 0xfff)
-                    + if addr == 0 {
-                        obj.segments
-                            .iter()
-                            .map(|s| &fold.segments[*s])
-                            .filter(|s| s.tag == PT_LOAD)
-                            .map(|s| s.vaddr + s.mem_size)
-                            .max()
-                            .unwrap_or(0)
-                    } else {
-                        0
-                    },
-                ProtFlags::READ | ProtFlags::WRITE | ProtFlags::EXEC,
-                MapFlags::PRIVATE
-                    | if addr == 0 {
-                        MapFlags::empty()
-                    } else {
-                        MapFlags::FIXED
-                    },
-            )
-            .expect("Anonymous mapping failed");
+        let base = match obj.shared.get(SYSV_LOADER_BASE_ADDR) {
+            Ok(base) => *base,
+            Err(_) => {
+                let base = reserve(obj, fold).map_err(|errno| SysvError::Mapping {
+                    object: obj.path.clone(),
+                    errno,
+                })?;
+                fold.objects[s.obj]
+                    .shared
+                    .insert(SYSV_LOADER_BASE_ADDR, base);
+                base
+            }
+        };
 
-            log::info!("Segment loaded at 0x{:x}", mapping as usize);
+        let (new_mapping, mapped_len) = unsafe {
+            // Make the pages of the segment accessible within the reservation. They are protected according to the
+            // segment flags by a later phase.
+            let addr = s.vaddr + base;
+            let start = addr & !(PAGE_SIZE - 1);
+            let len = (addr + s.mem_size).next_multiple_of(PAGE_SIZE) - start;
+            mm::mprotect(
+                start as *mut c_void,
+                len,
+                MprotectFlags::READ | MprotectFlags::WRITE | MprotectFlags::EXEC,
+            )
+            .map_err(|errno| SysvError::Mapping {
+                object: fold.objects[s.obj].path.clone(),
+                errno,
+            })?;
 
-            // Store a MappingMut instead ?
-            if obj.shared.get(SYSV_LOADER_BASE_ADDR).is_none() {
-                obj.shared.insert(
-                    SYSV_LOADER_BASE_ADDR,
-                    // If addr == 0, probably PIE
-                    if addr == 0 { mapping as usize } else { 0 },
-                )
-            }
+            log::info!("Segment loaded at 0x{addr:x}");
 
-            let mapping_start = mapping.add(addr & 0xfff);
+            let mapping_start = addr as *mut c_void;
 
             // Copy segment data
             mapping_start.copy_from(s.mapping.bytes().as_ptr() as *mut c_void, s.file_size);
@@ -100,13 +112,117 @@ impl Module for SysvLoader {
                     .write_bytes(0, s.mem_size - s.file_size);
             }
 
-            MappingMut::new(mapping_start as *mut u8, s.mem_size)
+            (MappingMut::new(mapping_start as *mut u8, s.mem_size), len)
         };
 
         fold.segments[segment]
             .shared
             .insert(SYSV_LOADER_MAPPING, new_mapping);
+        if let Ok(stats) = fold.shared.get_mut(FOLD_STATS_KEY) {
+            stats.count_mapped(mapped_len);
+        }
 
         Ok(())
     }
 }
+
+/// Reserves the address range spanned by the `PT_LOAD` segments of `obj`, and returns the base address of the object.
+///
+/// The reservation is inaccessible (`PROT_NONE`): segments are made accessible when they are loaded, and the gaps
+/// between them stay inaccessible. Position independent objects (`ET_DYN`) are placed at an address aligned to the
+/// largest alignment of their segments. Other objects are reserved at their own addresses, without replacing existing
+/// mappings: the range is only taken over if it holds the kernel's mapping of this very executable.
+fn reserve(obj: &Object, manifold: &Manifold) -> Result<usize, Errno> {
+    let segments = obj
+        .segments
+        .iter()
+        .map(|s| &manifold[*s])
+        .filter(|s| s.tag == PT_LOAD && s.mem_size != 0);
+    // Objects which are not position independent can not be moved to honor the alignment.
+    let align = if obj.elf_type == ET_DYN {
+        segments
+            .clone()
+            .map(|s| s.align)
+            .filter(|align| align.is_power_of_two())
+            .fold(PAGE_SIZE, usize::max)
+    } else {
+        PAGE_SIZE
+    };
+    let start = segments.clone().map(|s| s.vaddr).min().unwrap_or(0) & !(align - 1);
+    let end = segments
+        .map(|s| s.vaddr + s.mem_size)
+        .max()
+        .unwrap_or(0)
+        .next_multiple_of(PAGE_SIZE);
+    let len = end - start;
+
+    if obj.elf_type != ET_DYN {
+        let flags = MapFlags::PRIVATE | MapFlags::NORESERVE | MapFlags::FIXED_NOREPLACE;
+        let reservation =
+            unsafe { mm::mmap_anonymous(start as *mut c_void, len, ProtFlags::empty(), flags) };
+        match reservation {
+            Ok(addr) if addr as usize == start => {}
+            Ok(addr) => {
+                // Kernels before 4.17 ignore `MAP_FIXED_NOREPLACE`, and use the address as a hint.
+                unsafe { mm::munmap(addr, len)? };
+                return Err(Errno::EXIST);
+            }
+            Err(Errno::EXIST) if mapped_by_kernel(obj, manifold, start..end) => {
+                // The kernel already mapped the executable at its addresses, take them over.
+                log::warn!(
+                    "Address range 0x{start:x}-0x{end:x} of {} is already mapped by the kernel",
+                    obj.display_path()
+                );
+                unsafe {
+                    mm::mmap_anonymous(
+                        start as *mut c_void,
+                        len,
+                        ProtFlags::empty(),
+                        MapFlags::PRIVATE | MapFlags::NORESERVE | MapFlags::FIXED,
+                    )?
+                };
+            }
+            Err(errno) => return Err(errno),
+        }
+        log::info!("Reserved 0x{start:x}-0x{end:x} for {}", obj.display_path());
+        return Ok(0);
+    }
+
+    // Over-allocate such that an aligned range of `len` bytes fits, then release the excess.
+    let padded = len + align - PAGE_SIZE;
+    let reservation = unsafe {
+        mm::mmap_anonymous(
+            null_mut(),
+            padded,
+            ProtFlags::empty(),
+            MapFlags::PRIVATE | MapFlags::NORESERVE,
+        )?
+    } as usize;
+    let aligned = reservation.next_multiple_of(align);
+    unsafe {
+        if aligned > reservation {
+            mm::munmap(reservation as *mut c_void, aligned - reservation)?;
+        }
+        let tail = reservation + padded - (aligned + len);
+        if tail > 0 {
+            mm::munmap((aligned + len) as *mut c_void, tail)?;
+        }
+    }
+
+    log::info!(
+        "Reserved 0x{aligned:x}-0x{:x} for {}",
+        aligned + len,
+        obj.display_path()
+    );
+    Ok(aligned - start)
+}
+
+/// Returns `true` if `range` holds the kernel's mapping of `obj`, which is the case when the linker was started as the
+/// interpreter of `obj`: the auxiliary vector then describes its entry point and program headers.
+fn mapped_by_kernel(obj: &Object, manifold: &Manifold, range: Range<usize>) -> bool {
+    let env = &manifold.env;
+    let entry = env.get_auxv(AuxvType::ENTRY);
+    let phdr = env.get_auxv(AuxvType::PHDR);
+
+    entry == Some(obj.header().e_entry) && phdr.is_some_and(|phdr| range.contains(&(phdr as usize)))
+}
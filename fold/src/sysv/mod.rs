@@ -4,6 +4,9 @@
 
 pub mod collector;
 pub mod error;
+pub mod init;
+pub mod lazy;
+pub mod ldcache;
 pub mod loader;
 pub mod protect;
 pub mod relocation;
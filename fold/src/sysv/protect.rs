@@ -5,9 +5,11 @@ use goblin::elf::program_header::{PF_R, PF_W, PF_X};
 use rustix::mm::{self, MprotectFlags};
 
 use crate::arena::Handle;
+use crate::contract::{Contract, KeyScope};
 use crate::manifold::Manifold;
 use crate::module::Module;
 use crate::object::Segment;
+use crate::ordering::{Constraints, TAG_PROTECTION};
 use crate::sysv::loader::SYSV_LOADER_MAPPING;
 
 #[derive(Default)]
@@ -32,6 +34,14 @@ impl Module for SysvProtect {
         "sysv-protect"
     }
 
+    fn constraints(&self) -> Constraints {
+        Constraints::new().tag(TAG_PROTECTION)
+    }
+
+    fn contract(&self) -> Contract {
+        Contract::new().requires(KeyScope::Segment, SYSV_LOADER_MAPPING)
+    }
+
     fn process_segment(
         &mut self,
         segment: Handle<Segment>,
@@ -40,7 +50,7 @@ impl Module for SysvProtect {
         log::info!("Protecting segment...");
         let segment = &fold.segments[segment];
 
-        if let Some(mapping) = segment.shared.get(SYSV_LOADER_MAPPING) {
+        if let Ok(mapping) = segment.shared.get(SYSV_LOADER_MAPPING) {
             if segment.mem_size == 0 {
                 return Ok(());
             }
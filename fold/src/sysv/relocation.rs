@@ -1,4 +1,6 @@
+use alloc::borrow::ToOwned;
 use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
 use alloc::ffi::CString;
 use alloc::vec::Vec;
 use core::cell::LazyCell;
@@ -11,11 +13,16 @@ use goblin::elf64::reloc::{self, Rela};
 use log::info;
 
 use crate::arena::Handle;
+use crate::contract::{Contract, KeyScope};
 use crate::elf::ElfItemIterator;
 use crate::manifold::Manifold;
 use crate::module::Module;
-use crate::object::{Object, Section, SectionT};
+use crate::object::{Object, Section, SectionT, VER_FLG_WEAK};
+use crate::ordering::{Constraints, TAG_RELOCATION};
+use crate::scope::{Scope, SymbolLookup};
+use crate::stats::FOLD_STATS_KEY;
 use crate::sysv::error::SysvError;
+use crate::sysv::lazy::{self, LazyPlt};
 use crate::sysv::loader::SYSV_LOADER_BASE_ADDR;
 
 macro_rules! apply_reloc {
@@ -27,9 +34,13 @@ macro_rules! apply_reloc {
 
 // ———————————————————————————————— Library relocation ————————————————————————————————— //
 
+/// Applies the relocations of the objects it processes and of their dependencies.
+///
+/// Objects are queued while the phase is processed, and relocated at the end of the phase, dependencies first. Each
+/// object is relocated only once, even if several processed objects depend on it.
 #[derive(Default)]
 pub struct SysvReloc {
-    relocated: Vec<Handle<Object>>,
+    queue: Vec<Handle<Object>>,
 }
 
 impl SysvReloc {
@@ -38,14 +49,21 @@ impl SysvReloc {
     }
 }
 
-/// Return dependences of object and its dependencies
-fn add_deps(obj: &Object, manifold: &Manifold) -> Vec<Handle<Object>> {
-    let mut queue = Vec::new();
-    for dep in obj.dependencies.iter() {
-        queue.push(*dep);
-        queue.extend(add_deps(&manifold[*dep], manifold));
+/// Appends `obj` and its dependencies to `order` in post-order, skipping already present objects.
+pub(crate) fn add_deps(obj: Handle<Object>, manifold: &Manifold, order: &mut Vec<Handle<Object>>) {
+    let Some(object) = manifold.objects.get(obj) else {
+        return;
+    };
+    if order.contains(&obj) {
+        return;
+    }
+    // Mark the object first to stop on dependency cycles, then move it after its dependencies.
+    order.push(obj);
+    for dep in object.dependencies.iter() {
+        add_deps(*dep, manifold, order);
     }
-    queue
+    order.retain(|o| *o != obj);
+    order.push(obj);
 }
 
 impl Module for SysvReloc {
@@ -53,38 +71,136 @@ impl Module for SysvReloc {
         "sysv-reloc-lib"
     }
 
+    fn constraints(&self) -> Constraints {
+        Constraints::new().tag(TAG_RELOCATION)
+    }
+
+    fn contract(&self) -> Contract {
+        Contract::new().requires(KeyScope::Object, SYSV_LOADER_BASE_ADDR)
+    }
+
+    fn begin_phase(&mut self, _manifold: &mut Manifold) -> Result<(), Box<dyn core::fmt::Debug>> {
+        self.queue.clear();
+        Ok(())
+    }
+
     fn process_object(
         &mut self,
         obj: Handle<Object>,
-        manifold: &mut Manifold,
+        _manifold: &mut Manifold,
     ) -> Result<(), Box<dyn core::fmt::Debug>> {
-        let mut tree: Vec<Handle<Object>> = Vec::new();
-
-        tree.push(obj);
-        tree.extend(add_deps(&manifold[obj], manifold));
-
-        for dep in tree.into_iter().rev() {
-            if !self.relocated.contains(&dep) {
-                self.relocated.push(dep);
-                let obj = manifold.objects.get(dep).unwrap();
-                for section in obj.sections.iter() {
-                    let section = &manifold[*section];
-                    if section.tag == SHT_RELA {
-                        process_reloc(obj, section, manifold)?;
-                    }
+        self.queue.push(obj);
+        Ok(())
+    }
+
+    fn end_phase(&mut self, manifold: &mut Manifold) -> Result<(), Box<dyn core::fmt::Debug>> {
+        let mut order = Vec::new();
+        for obj in core::mem::take(&mut self.queue) {
+            add_deps(obj, manifold, &mut order);
+        }
+
+        for obj in order.iter() {
+            check_versions(&manifold[*obj], manifold)?;
+        }
+
+        // The scope is shared by all the lookups of the phase.
+        let scope = manifold.scope();
+        let mut counts = BTreeMap::new();
+        let mut any_lazy = false;
+        for handle in order {
+            let obj = &manifold[handle];
+            let lazy = LazyPlt::of(obj, manifold);
+            for section in obj.sections.iter() {
+                let section = &manifold[*section];
+                if section.tag == SHT_RELA {
+                    let lazy = lazy.is_some_and(|plt| plt.is_jmprel(section.addr));
+                    process_reloc(obj, section, manifold, &scope, lazy, &mut counts)?;
                 }
             }
+
+            if let Some(plt) = lazy {
+                plt.install(handle, manifold);
+                any_lazy = true;
+            }
+        }
+        if any_lazy {
+            lazy::set_resolver(manifold, scope);
+        }
+
+        if let Ok(stats) = manifold.shared.get_mut(FOLD_STATS_KEY) {
+            stats.count_relocations(&counts);
         }
         Ok(())
     }
 }
 
+/// Checks that the dependencies of `obj` define the versions it requires.
+///
+/// Dependencies are matched by file name among all the loaded objects, such that a library loaded by another object
+/// is found as well. As with `ld.so`, dependencies defining no version at all only trigger a warning. Requirements on
+/// a dependency which is not loaded, such as glibc's `libc.so.6` replaced by musl, are ignored.
+fn check_versions(obj: &Object, manifold: &Manifold) -> Result<(), SysvError> {
+    for requirement in obj.version_requirements(manifold) {
+        let Some(dep) = manifold
+            .objects
+            .enumerate()
+            .map(|(_, dep)| dep)
+            .find(|dep| {
+                dep.path.to_bytes().rsplit(|b| *b == b'/').next()
+                    == Some(requirement.file.to_bytes())
+            })
+        else {
+            log::debug!(
+                "Skipping version {:?} of {:?}, required by {}: dependency not loaded",
+                requirement.name,
+                requirement.file,
+                obj.display_path()
+            );
+            continue;
+        };
+
+        let mut definitions = dep.version_definitions(manifold).peekable();
+        if definitions.peek().is_none() {
+            log::warn!(
+                "{}: no version information available (required by {})",
+                dep.display_path(),
+                obj.display_path()
+            );
+            continue;
+        }
+        if definitions.any(|d| d.name == requirement.name) {
+            continue;
+        }
+
+        if requirement.flags & VER_FLG_WEAK != 0 {
+            log::warn!(
+                "{}: weak version {:?} not found (required by {})",
+                dep.display_path(),
+                requirement.name,
+                obj.display_path()
+            );
+            continue;
+        }
+        return Err(SysvError::MissingVersion {
+            version: requirement.name.to_owned(),
+            file: dep.path.clone(),
+            object: obj.path.clone(),
+        });
+    }
+
+    Ok(())
+}
+
 // ———————————————————————————————— Relocation ————————————————————————————————— //
 
+/// Applies the relocations of `section`. If `lazy` is set, jump slots are left pointing at their PLT stub.
 fn process_reloc(
     obj: &Object,
     section: &Section,
     manifold: &Manifold,
+    scope: &Scope,
+    lazy: bool,
+    counts: &mut BTreeMap<u32, usize>,
 ) -> Result<(), Box<dyn core::fmt::Debug>> {
     log::info!(
         "Process relocation of section {:?} for object {}...",
@@ -96,7 +212,7 @@ fn process_reloc(
         .shared
         .get(SYSV_LOADER_BASE_ADDR)
         .copied()
-        .ok_or(SysvError::RelaSectionWithoutVirtualAdresses)? as *mut u8;
+        .map_err(|_| SysvError::RelaSectionWithoutVirtualAdresses)? as *mut u8;
 
     let b = base as i64;
     let _got: i64 = obj
@@ -115,42 +231,27 @@ fn process_reloc(
         let sym = reloc::r_sym(rela.r_info);
 
         let a = rela.r_addend;
+        let lookup = LazyCell::new(|| SymbolLookup::reference(section.obj, sym as usize, manifold));
 
         // Lazily computed to avoid overhead if the relocation does not use the symbol's address.
         // Also, neat trick to warn for not found symbols only when the value is actually used.
         let s = LazyCell::new(|| {
-            let (s, name) = 's: {
-                // Get the symbol's name
-                let Some(name) = section
-                    .get_linked_section(manifold)
-                    .ok()
-                    .and_then(|s| s.as_dynamic_symbol_table().ok())
-                    .and_then(|s| s.get_symbol_name(sym as usize, manifold).ok())
-                else {
-                    break 's (None, None);
-                };
-
-                // Ignore empty symbols
-                if name.is_empty() {
-                    break 's (None, Some(name));
-                }
+            let name = lookup.as_ref().ok().map(|lookup| lookup.name);
 
-                // Find the related symbol in the loaded objects
-                (
-                    manifold
-                        .find_symbol(name, section.obj)
-                        .map(|(section, sym)| {
-                            manifold[section.obj]
-                                .shared
-                                .get(SYSV_LOADER_BASE_ADDR)
-                                .copied()
-                                .unwrap() as i64
-                                + sym.st_value as i64
-                        })
-                        .ok(),
-                    Some(name),
-                )
-            };
+            // Find the related symbol in the loaded objects, ignoring empty symbols
+            let s = lookup
+                .as_ref()
+                .ok()
+                .filter(|lookup| !lookup.name.is_empty())
+                .and_then(|lookup| manifold.resolve_symbol_in(scope, lookup).ok())
+                .map(|(section, sym)| {
+                    manifold[section.obj]
+                        .shared
+                        .get(SYSV_LOADER_BASE_ADDR)
+                        .copied()
+                        .unwrap() as i64
+                        + sym.st_value as i64
+                });
 
             s.unwrap_or_else(|| {
                 log::warn!("Unable to locate symbol {name:?}");
@@ -165,19 +266,13 @@ fn process_reloc(
                 apply_reloc!(addr, *s + a, u64);
             }
             R_X86_64_COPY => {
-                let name = section
-                    .get_linked_section(manifold)?
-                    .as_dynamic_symbol_table()?
-                    .get_symbol_name(sym as usize, manifold)?;
-
-                'find_symbol: for (_, lib_obj) in
-                    manifold.objects.enumerate().filter(|s| s.0 != section.obj)
-                {
-                    let Ok((_, lib_sym)) = lib_obj.find_symbol(name, manifold) else {
-                        continue;
-                    };
-
-                    let src = *lib_obj.shared.get(SYSV_LOADER_BASE_ADDR).unwrap_or(&0)
+                // The definition is searched in the other objects, the requester holding the copy.
+                let lookup = (*lookup).clone()?.copy();
+                if let Ok((container, lib_sym)) = manifold.resolve_symbol_in(scope, &lookup) {
+                    let src = *manifold[container.obj]
+                        .shared
+                        .get(SYSV_LOADER_BASE_ADDR)
+                        .unwrap_or(&0)
                         + lib_sym.st_value as usize;
 
                     unsafe {
@@ -186,10 +281,14 @@ fn process_reloc(
                             addr,
                             lib_sym.st_size as usize,
                         );
-                        break 'find_symbol;
                     }
                 }
             }
+            R_X86_64_JUMP_SLOT if lazy => {
+                // The slot holds the link-time address of the instruction following the jump of the PLT stub.
+                let stub = unsafe { core::ptr::read_unaligned(addr as *const u64) };
+                apply_reloc!(addr, b as u64 + stub, u64);
+            }
             R_X86_64_JUMP_SLOT => {
                 apply_reloc!(addr, *s, u64);
             }
@@ -198,30 +297,21 @@ fn process_reloc(
                     apply_reloc!(addr, *s, u64);
                 }
 
-                let name = section
-                    .get_linked_section(manifold)?
-                    .as_dynamic_symbol_table()?
-                    .get_symbol_name(sym as usize, manifold)?;
-
-                for (_, lib_obj) in manifold.objects.enumerate() {
-                    let Ok((container, lib_sym)) = lib_obj.find_dynamic_symbol(name, manifold)
-                    else {
-                        continue;
-                    };
-                    if lib_sym.st_value == 0 {
-                        continue;
-                    }
-
-                    let start = lib_sym.st_value as usize + container.offset - container.addr;
+                let lookup = (*lookup).clone()?;
+                if let Ok((container, lib_sym)) = manifold.resolve_symbol_in(scope, &lookup) {
+                    if lib_sym.st_value != 0 {
+                        let start = lib_sym.st_value as usize + container.offset - container.addr;
 
-                    let lib_content = lib_obj
-                        .shared
-                        .get(SYSV_LOADER_BASE_ADDR)
-                        .copied()
-                        .unwrap_or_default();
+                        let lib_content = manifold[container.obj]
+                            .shared
+                            .get(SYSV_LOADER_BASE_ADDR)
+                            .copied()
+                            .unwrap_or_default();
 
-                    apply_reloc!(addr, lib_content + start, u64);
-                    continue 'rela;
+                        apply_reloc!(addr, lib_content + start, u64);
+                        *counts.entry(r#type).or_default() += 1;
+                        continue 'rela;
+                    }
                 }
             }
             R_X86_64_32 | R_X86_64_32S => {
@@ -240,8 +330,13 @@ fn process_reloc(
                 let code: extern "C" fn() -> i64 = unsafe { core::mem::transmute(b + a) };
                 apply_reloc!(addr, code(), i64);
             }
-            _ => info!("unknown rela type {:#x}", r#type),
+            _ => {
+                info!("unknown rela type {:#x}", r#type);
+                continue 'rela;
+            }
         };
+
+        *counts.entry(r#type).or_default() += 1;
     }
 
     Ok(())
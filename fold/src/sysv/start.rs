@@ -2,13 +2,30 @@ use alloc::boxed::Box;
 use alloc::vec::Vec;
 use core::arch::asm;
 
+use goblin::elf::header::{ELFMAG, SELFMAG};
+use goblin::elf::program_header::{PT_LOAD, PT_PHDR};
+use plain::Plain;
+
+use super::init::SYSV_INIT_FINI_KEY;
 use super::loader::SYSV_LOADER_BASE_ADDR;
 use crate::arena::Handle;
-use crate::elf::Object;
+use crate::contract::{Contract, KeyScope};
+use crate::elf::{ElfHeader, Object, ProgramHeader};
+use crate::env::AuxvType;
 use crate::manifold::Manifold;
 use crate::module::Module;
+use crate::ordering::{Constraints, TAG_START};
+use crate::policy::FOLD_ERROR_REPORT_KEY;
+use crate::share_map::ShareMapKey;
+use crate::stats::FOLD_STATS_KEY;
 use crate::Env;
 
+/// Rewrites the entries of the auxiliary vector describing the program (`AT_PHDR`, `AT_PHNUM`, `AT_PHENT`,
+/// `AT_ENTRY`, `AT_BASE` and `AT_EXECFN`). Set when the linker is invoked explicitly, in which case the kernel
+/// describes the linker instead, and cleared once the vector is rewritten (see [`describe_program`]).
+pub const SYSV_START_REWRITE_AUXV_KEY: ShareMapKey<bool> =
+    ShareMapKey::new("sysv-start", "rewrite-auxv").with_debug();
+
 pub struct SysvStart;
 
 impl Module for SysvStart {
@@ -16,11 +33,20 @@ impl Module for SysvStart {
         "sysv-start"
     }
 
+    fn constraints(&self) -> Constraints {
+        Constraints::new().tag(TAG_START)
+    }
+
+    fn contract(&self) -> Contract {
+        Contract::new().requires(KeyScope::Object, SYSV_LOADER_BASE_ADDR)
+    }
+
     fn process_object(
         &mut self,
         obj: Handle<Object>,
         manifold: &mut Manifold,
     ) -> Result<(), Box<dyn core::fmt::Debug>> {
+        describe_program(obj, manifold);
         let obj = &manifold.objects[obj];
 
         let offset = obj
@@ -29,21 +55,34 @@ impl Module for SysvStart {
             .copied()
             .unwrap_or_default();
         let entry = obj.header().e_entry + offset as u64;
-
         let stack = build_stack(&manifold.env);
+        let fini = manifold
+            .shared
+            .get(SYSV_INIT_FINI_KEY)
+            .copied()
+            .unwrap_or_default();
+
+        // The chain does not return after the jump, report recovered errors and statistics now.
+        if let Ok(report) = manifold.shared.get(FOLD_ERROR_REPORT_KEY) {
+            report.log();
+        }
+        if let Ok(stats) = manifold.shared.get(FOLD_STATS_KEY) {
+            stats.report();
+        }
 
         unsafe {
             log::info!("Jumping at 0x{entry:x}...");
-            jmp(entry as *const u8, &stack);
+            jmp(entry as *const u8, &stack, fini);
         }
     }
 }
 
 // ————————————————————————————————— Utils —————————————————————————————————— //
 
-/// The actual jump tot he program entry
+/// The actual jump tot he program entry. As specified by the ABI, `rdx` holds the function the program must register
+/// with `atexit`, or `0`.
 #[inline(never)]
-unsafe fn jmp(entry_point: *const u8, stack: &[u64]) -> ! {
+unsafe fn jmp(entry_point: *const u8, stack: &[u64], fini: usize) -> ! {
     asm!(
         "2:",
         // loop if i isn't zero, break otherwise
@@ -64,6 +103,7 @@ unsafe fn jmp(entry_point: *const u8, stack: &[u64]) -> ! {
         stack_contents = in(reg) stack.as_ptr(),
         qword_count = in(reg) stack.len(),
         tmp = out(reg) _,
+        in("rdx") fini,
     );
 
     unreachable!();
@@ -87,10 +127,94 @@ pub fn build_stack(env: &Env) -> Vec<u64> {
     stack.push(null); // env is a null terminated array
 
     // Auxv
-    // TODO: add auxv
-    // for a in env.auxv.clone() {
-    //     stack.push(a.as_ptr() as u64);
-    // }
+    for aux in &env.auxv {
+        stack.push(aux.typ.value());
+        stack.push(aux.value);
+    }
+    stack.extend([AuxvType::NULL.value(), null]); // auxv is terminated by an AT_NULL entry
 
     stack
 }
+
+/// Describes `obj` in the auxiliary vector if [`SYSV_START_REWRITE_AUXV_KEY`] is set, then clears the key: the vector
+/// is rewritten once, by the first phase needing it.
+pub(crate) fn describe_program(obj: Handle<Object>, manifold: &mut Manifold) {
+    if manifold.shared.get(SYSV_START_REWRITE_AUXV_KEY) != Ok(&true) {
+        return;
+    }
+
+    let object = &manifold.objects[obj];
+    let base = object
+        .shared
+        .get(SYSV_LOADER_BASE_ADDR)
+        .copied()
+        .unwrap_or_default();
+    rewrite_auxv(object, base, &mut manifold.env);
+    manifold.shared.insert(SYSV_START_REWRITE_AUXV_KEY, false);
+}
+
+/// Describes the program loaded at `base` in the auxiliary vector, instead of the linker which was executed by the
+/// kernel. `AT_BASE` then holds the address of the linker, as if the kernel had loaded it as the interpreter.
+fn rewrite_auxv(obj: &Object, base: usize, env: &mut Env) {
+    if let Some(linker_base) = linker_base(env) {
+        env.set_auxv(AuxvType::BASE, linker_base);
+    }
+
+    // The program headers are found through the segment containing them. Otherwise, as the kernel does, they are
+    // assumed to follow the start of the file in the first segment.
+    let phdr = obj
+        .program_headers()
+        .find_map(|header| match header.p_type {
+            PT_PHDR => Some(header.p_vaddr),
+            PT_LOAD
+                if (header.p_offset..header.p_offset + header.p_filesz)
+                    .contains(&(obj.e_phoff as u64)) =>
+            {
+                Some(header.p_vaddr + obj.e_phoff as u64 - header.p_offset)
+            }
+            _ => None,
+        })
+        .or_else(|| {
+            obj.program_headers()
+                .find(|header| header.p_type == PT_LOAD)
+                .map(|header| header.p_vaddr - header.p_offset + obj.e_phoff as u64)
+        })
+        .unwrap_or(obj.e_phoff as u64);
+    env.set_auxv(AuxvType::PHDR, base as u64 + phdr);
+    env.set_auxv(AuxvType::PHENT, obj.e_phentsize as u64);
+    env.set_auxv(AuxvType::PHNUM, obj.e_phnum as u64);
+    env.set_auxv(AuxvType::ENTRY, obj.header().e_entry + base as u64);
+    env.set_auxv(AuxvType::EXECFN, obj.path.as_ptr() as u64);
+}
+
+/// Returns the address the linker was loaded at, computed from the program headers the kernel gave to the linker.
+///
+/// Without a `PT_PHDR` header, the program headers must directly follow the ELF header at the start of the segment
+/// mapping the beginning of the file, which is checked against the ELF header found there.
+fn linker_base(env: &Env) -> Option<u64> {
+    let phdr = env.get_auxv(AuxvType::PHDR)?;
+    let phnum = env.get_auxv(AuxvType::PHNUM)? as usize;
+    let headers = unsafe {
+        core::slice::from_raw_parts(
+            phdr as *const u8,
+            phnum * core::mem::size_of::<ProgramHeader>(),
+        )
+    };
+    let headers = ProgramHeader::slice_from_bytes(headers).ok()?;
+
+    if let Some(header) = headers.iter().find(|header| header.p_type == PT_PHDR) {
+        return Some(phdr - header.p_vaddr);
+    }
+
+    let first = headers
+        .iter()
+        .find(|header| header.p_type == PT_LOAD && header.p_offset == 0)?;
+    let ehdr_size = core::mem::size_of::<ElfHeader>() as u64;
+    if first.p_filesz < ehdr_size + (phnum * core::mem::size_of::<ProgramHeader>()) as u64 {
+        return None;
+    }
+
+    let ehdr = unsafe { &*((phdr - ehdr_size) as *const ElfHeader) };
+    (ehdr.e_ident[..SELFMAG] == ELFMAG[..] && ehdr.e_phoff == ehdr_size)
+        .then(|| phdr - ehdr_size - first.p_vaddr)
+}
@@ -6,22 +6,39 @@ use core::ptr::null_mut;
 use core::slice::from_raw_parts_mut;
 
 use rustix::mm::{mmap_anonymous, MapFlags, ProtFlags};
+use rustix::rand::{getrandom, GetRandomFlags};
 use zerocopy::FromBytes;
 
-use crate::musl::{Libc, RobustList, ThreadControlBlock, MUSL_LIBC_KEY};
+use crate::contract::{Contract, KeyScope};
+use crate::env::{AuxvType, Env};
+use crate::musl::{Libc, RobustList, ThreadControlBlock, MUSL_LIBC_KEY, MUSL_STACK_GUARD_KEY};
+use crate::ordering::{Constraints, TAG_TLS};
 use crate::sysv::tls::collection::{TlsModule, TLS_MODULES_KEY};
 use crate::sysv::tls::{set_fs, MuslTlsModule, TlsError, MUSL_TLS_MODULES_LL_KEY};
 use crate::{Manifold, Module, ShareMapKey};
 
 pub struct TlsAllocator;
 
-pub const TLS_TCB: ShareMapKey<&'static mut ThreadControlBlock> = ShareMapKey::new("tls-tcb-ptr");
+pub const TLS_TCB: ShareMapKey<&'static mut ThreadControlBlock> =
+    ShareMapKey::new("tls-allocator", "tcb");
 
 impl Module for TlsAllocator {
     fn name(&self) -> &'static str {
         "tls-allocator"
     }
 
+    fn constraints(&self) -> Constraints {
+        Constraints::new().tag(TAG_TLS)
+    }
+
+    fn contract(&self) -> Contract {
+        Contract::new()
+            .requires(KeyScope::Manifold, MUSL_LIBC_KEY)
+            .requires(KeyScope::Manifold, MUSL_STACK_GUARD_KEY)
+            .provides(KeyScope::Manifold, MUSL_TLS_MODULES_LL_KEY)
+            .provides(KeyScope::Manifold, TLS_TCB)
+    }
+
     fn process_manifold(&mut self, manifold: &mut Manifold) -> Result<(), Box<dyn Debug>> {
         let modules = manifold.shared.get(TLS_MODULES_KEY);
         let modules = modules.iter().flat_map(|v| v.iter());
@@ -36,10 +53,15 @@ impl Module for TlsAllocator {
             0
         };
 
-        let Some(libc) = manifold.shared.get(MUSL_LIBC_KEY).cloned() else {
+        let Ok(libc) = manifold.shared.get(MUSL_LIBC_KEY).cloned() else {
             log::warn!("MUSL not found, skipping TLS allocation");
             return Ok(());
         };
+        let canary = stack_canary(&manifold.env);
+        if let Ok(guard) = manifold.shared.get(MUSL_STACK_GUARD_KEY).cloned() {
+            *guard.get_mut(&mut manifold.segments)? = canary;
+        }
+
         let libc = libc.get_mut(&mut manifold.segments)?;
         libc.can_do_threads = 1;
         libc.tls_cnt = 1;
@@ -47,7 +69,7 @@ impl Module for TlsAllocator {
         libc.tls_align = tls.align;
         libc.tls_head = tls_head;
 
-        build_tcb(&mut tls, libc);
+        build_tcb(&mut tls, libc, canary);
 
         let ptr = tls.tcb as *mut ThreadControlBlock as usize;
 
@@ -167,7 +189,29 @@ where
     tls_head.map(|h| *h)
 }
 
-fn build_tcb(tls: &mut TlsBlock, libc: &Libc) {
+/// Returns the canary checked by the functions compiled with a stack protector.
+///
+/// It is read from the random bytes given by the kernel (`AT_RANDOM`), or from `getrandom` if they are missing. As
+/// musl does, its second byte is zeroed such that string functions can neither leak nor overwrite it, while
+/// off-by-one overflows are still detected.
+fn stack_canary(env: &Env) -> u64 {
+    let mut bytes = [0u8; 8];
+    match env.get_auxv(AuxvType::RANDOM) {
+        Some(random) if random != 0 => {
+            bytes = unsafe { (random as *const [u8; 8]).read_unaligned() };
+        }
+        _ => {
+            if let Err(err) = getrandom(&mut bytes, GetRandomFlags::empty()) {
+                log::warn!("Unable to generate the stack canary: {err}");
+            }
+        }
+    }
+
+    bytes[1] = 0;
+    u64::from_ne_bytes(bytes)
+}
+
+fn build_tcb(tls: &mut TlsBlock, libc: &Libc, canary: u64) {
     let tid: u32;
     unsafe {
         asm!(
@@ -183,7 +227,7 @@ fn build_tcb(tls: &mut TlsBlock, libc: &Libc) {
         prev: &raw mut *tls.tcb,
         next: &raw mut *tls.tcb,
         sysinfo: 0,
-        stack_guard: 0xDEADBEEF_u64, // TODO: randomly generated this
+        stack_guard: canary,
         tid,
         errno: 0,
         detach_state: 0x2, // DT_JOINABLE
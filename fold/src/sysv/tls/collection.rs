@@ -6,7 +6,9 @@ use alloc::vec::Vec;
 use goblin::elf::program_header::PT_TLS;
 
 use crate::arena::Handle;
+use crate::contract::{Contract, KeyScope};
 use crate::elf::{Object, Segment};
+use crate::ordering::{Constraints, TAG_TLS};
 use crate::sysv::tls::TlsError;
 use crate::{Manifold, Module, ShareMapKey, INITIAL_ELF_KEY};
 
@@ -38,14 +40,27 @@ pub struct TlsModule {
     pub segment: Handle<Segment>,
 }
 
-pub const TLS_MODULES_KEY: ShareMapKey<Vec<TlsModule>> = ShareMapKey::new("tls-modules");
-pub const TLS_MODULE_KEY: ShareMapKey<TlsModule> = ShareMapKey::new("tls-module");
+pub const TLS_MODULES_KEY: ShareMapKey<Vec<TlsModule>> =
+    ShareMapKey::new("tls-collector", "modules").with_debug();
+pub const TLS_MODULE_KEY: ShareMapKey<TlsModule> =
+    ShareMapKey::new("tls-collector", "module").with_debug();
 
 impl Module for TlsCollector {
     fn name(&self) -> &'static str {
         "tls-collector"
     }
 
+    fn constraints(&self) -> Constraints {
+        Constraints::new().tag(TAG_TLS)
+    }
+
+    fn contract(&self) -> Contract {
+        Contract::new()
+            .requires(KeyScope::Manifold, INITIAL_ELF_KEY)
+            .provides(KeyScope::Manifold, TLS_MODULES_KEY)
+            .provides(KeyScope::Object, TLS_MODULE_KEY)
+    }
+
     fn process_object(
         &mut self,
         obj: Handle<Object>,
@@ -67,7 +82,7 @@ impl Module for TlsCollector {
         let initial_elf = manifold
             .shared
             .get(INITIAL_ELF_KEY)
-            .ok_or(TlsError::MissingSharedMapEntry(INITIAL_ELF_KEY.key))?;
+            .map_err(TlsError::from)?;
 
         let id = if *initial_elf == obj {
             1
@@ -6,7 +6,7 @@ use core::ffi::c_void;
 use log::trace;
 use rustix::io::Errno;
 
-use crate::ShareMapKey;
+use crate::{ShareMapError, ShareMapKey};
 
 pub mod allocation;
 pub mod collection;
@@ -45,7 +45,7 @@ pub struct MuslTlsModule {
 }
 
 pub const MUSL_TLS_MODULES_LL_KEY: ShareMapKey<MuslTlsModule> =
-    ShareMapKey::new("musl-tls-modules");
+    ShareMapKey::new("tls-allocator", "musl-modules");
 
 const PAGE_SIZE: usize = 1 << 12;
 
@@ -53,7 +53,7 @@ const PAGE_SIZE: usize = 1 << 12;
 pub enum TlsError {
     Linux(Errno),
     InvalidModuleId(usize),
-    MissingSharedMapEntry(&'static str),
+    SharedMap(ShareMapError),
 }
 
 impl From<Errno> for TlsError {
@@ -62,6 +62,12 @@ impl From<Errno> for TlsError {
     }
 }
 
+impl From<ShareMapError> for TlsError {
+    fn from(value: ShareMapError) -> Self {
+        Self::SharedMap(value)
+    }
+}
+
 impl From<TlsError> for Box<dyn Debug> {
     fn from(value: TlsError) -> Self {
         Box::new(value) as Self
@@ -1,4 +1,5 @@
 use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
 use core::cell::LazyCell;
 use core::fmt::Debug;
 use core::ptr::write_unaligned;
@@ -10,13 +11,27 @@ use goblin::elf::reloc::{
 use goblin::elf64::reloc::{self, Rela};
 
 use crate::arena::Handle;
+use crate::contract::{Contract, KeyScope};
 use crate::elf::{ElfItemIterator, Section, SectionT};
+use crate::ordering::{Constraints, TAG_RELOCATION, TAG_TLS};
+use crate::stats::FOLD_STATS_KEY;
 use crate::sysv::loader::SYSV_LOADER_BASE_ADDR;
 use crate::sysv::tls::collection::TLS_MODULE_KEY;
 use crate::sysv::tls::TlsError;
-use crate::{Manifold, Module};
+use crate::{Manifold, Module, Scope, SymbolLookup};
 
-pub struct TlsRelocator;
+/// Applies the thread-local relocations.
+#[derive(Default)]
+pub struct TlsRelocator {
+    /// Scope of the lookups of the phase.
+    scope: Scope,
+}
+
+impl TlsRelocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
 
 const TLS_RELOCS: &[u32] = &[
     R_X86_64_DTPMOD64,
@@ -34,6 +49,21 @@ impl Module for TlsRelocator {
         "tls-relocation"
     }
 
+    fn constraints(&self) -> Constraints {
+        Constraints::new().tag(TAG_TLS).tag(TAG_RELOCATION)
+    }
+
+    fn contract(&self) -> Contract {
+        Contract::new()
+            .requires(KeyScope::Object, SYSV_LOADER_BASE_ADDR)
+            .requires(KeyScope::Object, TLS_MODULE_KEY)
+    }
+
+    fn begin_phase(&mut self, manifold: &mut Manifold) -> Result<(), Box<dyn Debug>> {
+        self.scope = manifold.scope();
+        Ok(())
+    }
+
     fn process_section(
         &mut self,
         section: Handle<Section>,
@@ -45,9 +75,9 @@ impl Module for TlsRelocator {
             .shared
             .get(SYSV_LOADER_BASE_ADDR)
             .copied()
-            .ok_or(TlsError::MissingSharedMapEntry(SYSV_LOADER_BASE_ADDR.key))?
-            as *mut u8;
+            .map_err(TlsError::from)? as *mut u8;
 
+        let mut counts = BTreeMap::new();
         for rela in ElfItemIterator::<Rela>::from_section(section) {
             let addr = unsafe { base.add(rela.r_offset as usize) };
             let r#type = reloc::r_type(rela.r_info);
@@ -67,7 +97,11 @@ impl Module for TlsRelocator {
                     .unwrap()
             });
 
-            let orig = LazyCell::new(|| manifold.find_symbol(*name, section.obj).unwrap());
+            let orig = LazyCell::new(|| {
+                SymbolLookup::reference(section.obj, sym as usize, manifold)
+                    .and_then(|lookup| manifold.resolve_symbol_in(&self.scope, &lookup))
+                    .unwrap()
+            });
 
             let tls_module = LazyCell::new(|| {
                 manifold[orig.0.obj]
@@ -109,8 +143,13 @@ impl Module for TlsRelocator {
                 R_X86_64_GOTTPOFF | R_X86_64_TLSGD | R_X86_64_TLSLD => todo!(),
                 _ => unreachable!(),
             }
+
+            *counts.entry(r#type).or_default() += 1;
         }
 
+        if let Ok(stats) = manifold.shared.get_mut(FOLD_STATS_KEY) {
+            stats.count_relocations(&counts);
+        }
         Ok(())
     }
 }
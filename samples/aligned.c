@@ -0,0 +1,7 @@
+#include <stdint.h>
+
+static int aligned_value __attribute__((aligned(0x200000))) = 42;
+
+int is_aligned(void) {
+  return (uintptr_t)&aligned_value % 0x200000 == 0 && aligned_value == 42;
+}
@@ -0,0 +1,12 @@
+#include <stdio.h>
+
+int lib_initialized = 0;
+
+__attribute__((constructor)) static void lib_ctor(void) {
+  puts("lib ctor");
+  lib_initialized = 1;
+}
+
+__attribute__((destructor)) static void lib_dtor(void) {
+  puts("lib dtor");
+}
@@ -0,0 +1,12 @@
+#include <stdio.h>
+
+static int initialized = 0;
+
+__attribute__((constructor)) static void base_ctor(void) {
+  puts("base ctor");
+  initialized = 1;
+}
+
+int base_initialized(void) {
+  return initialized;
+}
@@ -0,0 +1,11 @@
+#include <stdio.h>
+
+int base_initialized(void);
+
+__attribute__((constructor)) static void left_ctor(void) {
+  printf("left ctor: %d\n", base_initialized());
+}
+
+const char* left_name(void) {
+  return "left";
+}
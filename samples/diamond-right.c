@@ -0,0 +1,11 @@
+#include <stdio.h>
+
+int base_initialized(void);
+
+__attribute__((constructor)) static void right_ctor(void) {
+  printf("right ctor: %d\n", base_initialized());
+}
+
+const char* right_name(void) {
+  return "right";
+}
@@ -0,0 +1,15 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+static const char* show(const char* value) {
+  return value ? value : "(unset)";
+}
+
+int main(int argc, char** argv) {
+  for (int i = 1; i < argc; i++) {
+    printf("arg: %s\n", argv[i]);
+  }
+  printf("NAME: %s\n", show(getenv("NAME")));
+  printf("ENV_LINKER_REMOVED: %s\n", show(getenv("ENV_LINKER_REMOVED")));
+  return 0;
+}
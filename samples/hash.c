@@ -0,0 +1,8 @@
+// Enough symbols for the hash tables to have several buckets and chains.
+int hash_alpha(void) { return 1; }
+int hash_bravo(void) { return 2; }
+int hash_charlie(void) { return 3; }
+int hash_delta(void) { return 4; }
+int hash_echo(void) { return 5; }
+int hash_foxtrot(void) { return 6; }
+int hash_golf(void) { return 7; }
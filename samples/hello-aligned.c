@@ -0,0 +1,8 @@
+#include <stdio.h>
+
+int is_aligned(void);
+
+int main() {
+  printf("aligned: %d\n", is_aligned());
+  return 0;
+}
@@ -0,0 +1,16 @@
+#include <elf.h>
+#include <stdio.h>
+#include <sys/auxv.h>
+
+extern char _start[];
+extern const Elf64_Ehdr __ehdr_start;
+
+int main() {
+  const char *phdr = (const char *)&__ehdr_start + __ehdr_start.e_phoff;
+  printf("entry: %d\n", getauxval(AT_ENTRY) == (unsigned long)_start);
+  printf("phdr: %d\n", getauxval(AT_PHDR) == (unsigned long)phdr);
+  printf("base: %d\n", getauxval(AT_BASE) != 0);
+  printf("pagesz: %lu\n", getauxval(AT_PAGESZ));
+  printf("random: %d\n", getauxval(AT_RANDOM) != 0);
+  return 0;
+}
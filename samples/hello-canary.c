@@ -0,0 +1,8 @@
+#include <stdio.h>
+
+int main() {
+  unsigned long canary;
+  __asm__("mov %%fs:0x28, %0" : "=r"(canary));
+  printf("canary: %016lx\n", canary);
+  return 0;
+}
@@ -0,0 +1,20 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/auxv.h>
+
+extern int lib_initialized;
+
+// The environment and the auxiliary vector are already set when constructors run.
+__attribute__((constructor)) static void main_ctor(void) {
+  const char* name = getenv("CTOR_NAME");
+  printf("main ctor: %d %s %d\n", lib_initialized, name ? name : "(unset)", getauxval(AT_PAGESZ) != 0);
+}
+
+__attribute__((destructor)) static void main_dtor(void) {
+  puts("main dtor");
+}
+
+int main() {
+  puts("main");
+  return 0;
+}
@@ -0,0 +1,10 @@
+#include <stdio.h>
+
+const char* left_name(void);
+const char* right_name(void);
+int base_initialized(void);
+
+int main() {
+  printf("main: %s %s %d\n", left_name(), right_name(), base_initialized());
+  return 0;
+}
@@ -0,0 +1,15 @@
+#include <stdio.h>
+
+int hash_alpha(void);
+int hash_bravo(void);
+int hash_charlie(void);
+int hash_delta(void);
+int hash_echo(void);
+int hash_foxtrot(void);
+int hash_golf(void);
+
+int main() {
+  int sum = hash_alpha() + hash_bravo() + hash_charlie() + hash_delta() + hash_echo() + hash_foxtrot() + hash_golf();
+  printf("hash: %d\n", sum);
+  return 0;
+}
@@ -0,0 +1,10 @@
+#include <stdio.h>
+
+const char* scope_name(void);
+const char* left_name(void);
+
+int main() {
+  printf("main: %s\n", scope_name());
+  printf("left: %s\n", left_name());
+  return 0;
+}
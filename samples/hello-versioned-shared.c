@@ -0,0 +1,10 @@
+#include <stdio.h>
+
+const char* version(void);
+const char* user_version(void);
+
+int main() {
+  printf("main: %s\n", version());
+  printf("user: %s\n", user_version());
+  return 0;
+}
@@ -0,0 +1,13 @@
+#include <stdio.h>
+
+const char* version(void);
+const char* version_1(void);
+
+// References the non-default version of `version`.
+__asm__(".symver version_1, version@VERS_1");
+
+int main() {
+  printf("default: %s\n", version());
+  printf("explicit: %s\n", version_1());
+  return 0;
+}
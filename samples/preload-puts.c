@@ -0,0 +1,10 @@
+#include <string.h>
+#include <unistd.h>
+
+// Interposes musl's `puts` when preloaded.
+int puts(const char* s) {
+  write(1, "preloaded: ", 11);
+  write(1, s, strlen(s));
+  write(1, "\n", 1);
+  return 0;
+}
@@ -0,0 +1,4 @@
+// Only reached after libscope-right.so in the breadth-first global scope.
+const char* scope_name(void) {
+  return "deep";
+}
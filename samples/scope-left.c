@@ -0,0 +1,6 @@
+const char* scope_name(void);
+
+// Binds through the global scope rather than to a dependency of this library.
+const char* left_name(void) {
+  return scope_name();
+}
@@ -0,0 +1,3 @@
+const char* scope_name(void) {
+  return "right";
+}
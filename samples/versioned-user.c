@@ -0,0 +1,6 @@
+const char* version(void);
+
+// Requires `VERS_2` of `libversioned.so`, which is also a dependency of the program.
+const char* user_version(void) {
+  return version();
+}
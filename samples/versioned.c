@@ -0,0 +1,11 @@
+// Two versions of `version`: new programs bind to the default `VERS_2`, older ones to `VERS_1`.
+__asm__(".symver version_1, version@VERS_1");
+__asm__(".symver version_2, version@@VERS_2");
+
+const char* version_1(void) {
+  return "version 1";
+}
+
+const char* version_2(void) {
+  return "version 2";
+}
@@ -37,6 +37,232 @@ mod tests {
         assert!(dbg!(String::from_utf8_lossy(&output.stdout)).contains("Missing name :/"));
     }
 
+    #[test]
+    fn hello_args_explicit_invocation() {
+        let output = Command::new("../target/x86_64-unknown-linux-none/debug/fold")
+            .args(["--log-level", "off", "--", "../samples/hello-args", "test"])
+            .output()
+            .expect("Failed to execute process");
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "Hello test !\n");
+    }
+
+    #[test]
+    fn stats_per_phase() {
+        let output = Command::new("../target/x86_64-unknown-linux-none/debug/fold")
+            .args(["--log-level=off", "--stats", "../samples/hello-c"])
+            .output()
+            .expect("Failed to execute process");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Fold statistics"));
+        assert!(stdout.contains(" calls, "));
+        // Elements are only reported with `--stats-elements`.
+        assert!(!stdout.contains("beginning of phase"));
+
+        let output = Command::new("../target/x86_64-unknown-linux-none/debug/fold")
+            .args([
+                "--log-level=off",
+                "--stats",
+                "--stats-elements",
+                "../samples/hello-c",
+            ])
+            .output()
+            .expect("Failed to execute process");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("beginning of phase"));
+        assert!(stdout.contains("hi there"));
+    }
+
+    #[test]
+    fn library_cache_order() {
+        // The system cache lists glibc's `libc.so` linker script, which must not shadow musl's `libc.so`, nor the
+        // libraries next to the target.
+        let output = Command::new("../target/x86_64-unknown-linux-none/debug/fold")
+            .args(["--log-level=off", "--list", "../samples/hello-versioned"])
+            .env_remove("FOLD_INHIBIT_CACHE")
+            .output()
+            .expect("Failed to execute process");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let path = |name: &str| {
+            stdout
+                .lines()
+                .find_map(|line| line.trim().strip_prefix(&format!("{name} => ")))
+                .map(|path| path.split(' ').next().unwrap().to_owned())
+        };
+        assert!(path("libc.so").is_some_and(|p| p.ends_with("/musl/lib/libc.so")));
+        assert_eq!(
+            path("libversioned.so").as_deref(),
+            Some("../samples/libversioned.so")
+        );
+    }
+
+    #[test]
+    fn preload() {
+        let output = Command::new("../target/x86_64-unknown-linux-none/debug/fold")
+            .args([
+                "--log-level=off",
+                "--preload",
+                "../samples/libpreload-puts.so",
+            ])
+            .arg("../samples/hello-c")
+            .output()
+            .expect("Failed to execute process");
+        assert!(String::from_utf8_lossy(&output.stdout).contains("preloaded: hi there"));
+    }
+
+    #[test]
+    fn symbol_versions() {
+        let output = Command::new("../target/x86_64-unknown-linux-none/debug/fold")
+            .args(["--log-level=off", "--library-path", "../samples"])
+            .arg("../samples/hello-versioned")
+            .output()
+            .expect("Failed to execute process");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("default: version 2"));
+        assert!(stdout.contains("explicit: version 1"));
+    }
+
+    #[test]
+    fn shared_symbol_versions() {
+        let output = Command::new("../target/x86_64-unknown-linux-none/debug/fold")
+            .args(["--log-level=off", "--library-path", "../samples"])
+            .arg("../samples/hello-versioned-shared")
+            .output()
+            .expect("Failed to execute process");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(stdout, "main: version 2\nuser: version 2\n");
+    }
+
+    #[test]
+    fn breadth_first_scope() {
+        let output = Command::new("../target/x86_64-unknown-linux-none/debug/fold")
+            .args(["--log-level=off", "--library-path", "../samples"])
+            .arg("../samples/hello-scope")
+            .output()
+            .expect("Failed to execute process");
+        assert!(output.status.success());
+        // `scope_name` is defined by the deep dependency and by the right one: the latter comes first in breadth-first
+        // order, for the program as well as for the left library.
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(stdout, "main: right\nleft: right\n");
+    }
+
+    #[test]
+    fn constructors() {
+        let output = Command::new("../target/x86_64-unknown-linux-none/debug/fold")
+            .args(["--log-level=off", "--library-path", "../samples"])
+            .arg("../samples/hello-ctor")
+            .env("CTOR_NAME", "test")
+            .output()
+            .expect("Failed to execute process");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        // Finalizers run in the opposite order, when the program exits.
+        assert_eq!(
+            stdout,
+            "lib ctor\nmain ctor: 1 test 1\nmain\nmain dtor\nlib dtor\n"
+        );
+    }
+
+    #[test]
+    fn shared_dependency_constructors() {
+        let output = Command::new("../target/x86_64-unknown-linux-none/debug/fold")
+            .args(["--log-level=off", "--library-path", "../samples"])
+            .arg("../samples/hello-diamond")
+            .output()
+            .expect("Failed to execute process");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(
+            stdout,
+            "base ctor\nleft ctor: 1\nright ctor: 1\nmain: left right 1\n"
+        );
+    }
+
+    #[test]
+    fn auxiliary_vector() {
+        let output = Command::new("../target/x86_64-unknown-linux-none/debug/fold")
+            .arg("--log-level=off")
+            .arg("../samples/hello-auxv")
+            .output()
+            .expect("Failed to execute process");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let page_size = Command::new("getconf")
+            .arg("PAGESIZE")
+            .output()
+            .expect("Failed to execute process");
+        let page_size = String::from_utf8_lossy(&page_size.stdout);
+        assert!(stdout.contains("entry: 1"));
+        assert!(stdout.contains("phdr: 1"));
+        assert!(stdout.contains("base: 1"));
+        assert!(stdout.contains(&format!("pagesz: {}", page_size.trim())));
+        assert!(stdout.contains("random: 1"));
+    }
+
+    #[test]
+    fn stack_canary() {
+        let canary = || {
+            let output = Command::new("../samples/hello-canary")
+                .output()
+                .expect("Failed to execute process");
+            assert!(output.status.success());
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        };
+
+        let first = canary();
+        assert_eq!(&first[first.len() - 5..first.len() - 3], "00");
+        assert!(!first.contains("deadbeef"));
+        assert_ne!(first, canary());
+    }
+
+    #[test]
+    fn segment_alignment() {
+        let output = Command::new("../target/x86_64-unknown-linux-none/debug/fold")
+            .args(["--log-level=off", "--library-path", "../samples"])
+            .arg("../samples/hello-aligned")
+            .output()
+            .expect("Failed to execute process");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("aligned: 1"));
+    }
+
+    #[test]
+    fn lazy_binding() {
+        let run = |lazy: bool| {
+            let mut command = Command::new("../target/x86_64-unknown-linux-none/debug/fold");
+            command
+                .arg("--log-level=trace")
+                .arg("../samples/hello-math");
+            if lazy {
+                command.env("FOLD_LAZY_BINDING", "1");
+            }
+            let output = command.output().expect("Failed to execute process");
+            assert!(output.status.success());
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        };
+
+        // Functions are bound by the resolver on their first call, and only when lazy binding is enabled.
+        let lazy = run(true);
+        assert!(lazy.contains("Lazily bound PLT relocation"));
+        assert!(lazy.contains("1.414214"));
+        let eager = run(false);
+        assert!(!eager.contains("Lazily bound PLT relocation"));
+        assert!(eager.contains("1.414214"));
+    }
+
+    #[test]
+    fn verify() {
+        let status = Command::new("../target/x86_64-unknown-linux-none/debug/fold")
+            .args(["--log-level=off", "--verify", "../samples/hello-c"])
+            .stdout(Stdio::null())
+            .status()
+            .expect("Failed to execute process");
+        assert!(status.success());
+    }
+
     #[test]
     fn hello_bss() {
         let output = Command::new("../samples/hello-bss")
@@ -119,4 +345,47 @@ mod tests {
         assert!(String::from_utf8_lossy(&output.stdout).contains("hi there\n"));
         assert!(String::from_utf8_lossy(&output.stdout).contains("from hook"));
     }
+
+    #[test]
+    fn env_edit() {
+        let output = Command::new("../samples/env-edit")
+            .args(["one", "two"])
+            .env("ENV_LINKER_REMOVED", "1")
+            .env_remove("NAME")
+            .output()
+            .expect("Failed to execute process");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success());
+        assert!(stdout.contains("All environment edits succeeded"));
+        assert!(stdout.contains(
+            "arg: first\narg: uno\narg: last\nNAME: edited\nENV_LINKER_REMOVED: (unset)\n"
+        ));
+    }
+
+    #[test]
+    fn gnu_hash_lookup() {
+        let output = Command::new("../samples/hello-hash-gnu")
+            .output()
+            .expect("Failed to execute process");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success());
+        assert!(
+            stdout
+                .contains("libhash-gnu.so: GNU hash lookups agree with a linear scan of 7 symbols")
+        );
+        assert!(stdout.contains("hash: 28\n"));
+    }
+
+    #[test]
+    fn sysv_hash_lookup() {
+        let output = Command::new("../samples/hello-hash-sysv")
+            .output()
+            .expect("Failed to execute process");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success());
+        assert!(stdout.contains(
+            "libhash-sysv.so: System V hash lookups agree with a linear scan of 7 symbols"
+        ));
+        assert!(stdout.contains("hash: 28\n"));
+    }
 }